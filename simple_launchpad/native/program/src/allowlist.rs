@@ -0,0 +1,253 @@
+//! Merkle allowlist used to gate the presale phase.
+//!
+//! Leaves commit to a buyer and the lamports they may contribute during the
+//! allowlist phase. Inner nodes hash their children in sorted order, so a proof
+//! is just the list of sibling hashes from the leaf up to the root.
+
+use solana_program::{keccak::hashv, pubkey::Pubkey};
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hashes the allowlist leaf for `buyer` with its `allocation`.
+pub fn leaf_hash(buyer: &Pubkey, allocation: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, buyer.as_ref(), &allocation.to_le_bytes()]).to_bytes()
+}
+
+/// Hashes two sibling nodes into their parent.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Returns whether `proof` links `leaf` to `root`.
+pub fn verify_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    computed == *root
+}
+
+#[cfg(not(target_os = "solana"))]
+pub use self::tree::*;
+
+#[cfg(not(target_os = "solana"))]
+mod tree {
+    use super::{leaf_hash, node_hash};
+    use crate::instruction::AllowlistProof;
+    use solana_program::pubkey::Pubkey;
+    use std::str::FromStr;
+    use thiserror::Error;
+
+    /// Errors raised while loading an allowlist CSV.
+    #[derive(Clone, Debug, Eq, Error, PartialEq)]
+    pub enum AllowlistCsvError {
+        #[error("line {0}: expected `address,allocation`")]
+        MalformedLine(usize),
+        #[error("line {0}: invalid address")]
+        InvalidAddress(usize),
+        #[error("line {0}: invalid allocation")]
+        InvalidAllocation(usize),
+        #[error("line {0}: duplicate address")]
+        DuplicateAddress(usize),
+        #[error("allowlist is empty")]
+        Empty,
+    }
+
+    /// Off-chain Merkle tree over the allowlist entries.
+    #[derive(Clone, Debug)]
+    pub struct AllowlistTree {
+        entries: Vec<(Pubkey, u64)>,
+        layers: Vec<Vec<[u8; 32]>>,
+    }
+
+    impl AllowlistTree {
+        /// Builds the tree from `(buyer, allocation)` pairs.
+        pub fn new(entries: Vec<(Pubkey, u64)>) -> Self {
+            let mut layers = vec![entries
+                .iter()
+                .map(|(buyer, allocation)| leaf_hash(buyer, *allocation))
+                .collect::<Vec<_>>()];
+            while layers[layers.len() - 1].len() > 1 {
+                let next = layers[layers.len() - 1]
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [a, b] => node_hash(a, b),
+                        [a] => *a,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                layers.push(next);
+            }
+            Self { entries, layers }
+        }
+
+        /// Parses `address,allocation` lines, skipping blank lines, `#` comments
+        /// and an optional header row.
+        pub fn from_csv(csv: &str) -> Result<Self, AllowlistCsvError> {
+            let mut entries: Vec<(Pubkey, u64)> = Vec::new();
+            for (index, line) in csv.lines().enumerate() {
+                let line_number = index + 1;
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (address, allocation) = line
+                    .split_once(',')
+                    .ok_or(AllowlistCsvError::MalformedLine(line_number))?;
+                let (address, allocation) = (address.trim(), allocation.trim());
+                if entries.is_empty() && address.eq_ignore_ascii_case("address") {
+                    continue;
+                }
+                let buyer = Pubkey::from_str(address)
+                    .map_err(|_| AllowlistCsvError::InvalidAddress(line_number))?;
+                let allocation = allocation
+                    .parse::<u64>()
+                    .map_err(|_| AllowlistCsvError::InvalidAllocation(line_number))?;
+                if entries.iter().any(|(existing, _)| *existing == buyer) {
+                    return Err(AllowlistCsvError::DuplicateAddress(line_number));
+                }
+                entries.push((buyer, allocation));
+            }
+            if entries.is_empty() {
+                return Err(AllowlistCsvError::Empty);
+            }
+            Ok(Self::new(entries))
+        }
+
+        /// Root to store as `ProjectInfo::allowlist_root`.
+        pub fn root(&self) -> [u8; 32] {
+            self.layers
+                .last()
+                .and_then(|layer| layer.first())
+                .copied()
+                .unwrap_or_default()
+        }
+
+        /// Proof to attach to `buyer`'s `Mint` instructions during the allowlist phase.
+        pub fn proof(&self, buyer: &Pubkey) -> Option<AllowlistProof> {
+            let mut index = self.entries.iter().position(|(entry, _)| entry == buyer)?;
+            let allocation = self.entries[index].1;
+            let mut proof = Vec::new();
+            for layer in &self.layers[..self.layers.len() - 1] {
+                if let Some(sibling) = layer.get(index ^ 1) {
+                    proof.push(*sibling);
+                }
+                index /= 2;
+            }
+            Some(AllowlistProof { allocation, proof })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: usize) -> Vec<(Pubkey, u64)> {
+        (0..count)
+            .map(|index| (Pubkey::new_unique(), 1_000 * (index as u64 + 1)))
+            .collect()
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf_count() {
+        for count in 1..=9 {
+            let entries = entries(count);
+            let tree = AllowlistTree::new(entries.clone());
+            for (buyer, allocation) in &entries {
+                let proof = tree.proof(buyer).unwrap();
+                assert_eq!(proof.allocation, *allocation);
+                let leaf = leaf_hash(buyer, *allocation);
+                assert!(
+                    verify_proof(&tree.root(), leaf, &proof.proof),
+                    "{} leaves",
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_leaf_is_the_root() {
+        let entries = entries(1);
+        let tree = AllowlistTree::new(entries.clone());
+        let (buyer, allocation) = entries[0];
+        assert_eq!(tree.root(), leaf_hash(&buyer, allocation));
+        assert!(tree.proof(&buyer).unwrap().proof.is_empty());
+    }
+
+    #[test]
+    fn rejects_wrong_allocation_buyer_or_root() {
+        let entries = entries(5);
+        let tree = AllowlistTree::new(entries.clone());
+        let (buyer, allocation) = entries[4];
+        let proof = tree.proof(&buyer).unwrap().proof;
+        assert!(!verify_proof(
+            &tree.root(),
+            leaf_hash(&buyer, allocation + 1),
+            &proof
+        ));
+        assert!(!verify_proof(
+            &tree.root(),
+            leaf_hash(&Pubkey::new_unique(), allocation),
+            &proof
+        ));
+        assert!(!verify_proof(
+            &[0; 32],
+            leaf_hash(&buyer, allocation),
+            &proof
+        ));
+        assert!(tree.proof(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn leaves_cannot_pose_as_inner_nodes() {
+        let entries = entries(2);
+        let tree = AllowlistTree::new(entries.clone());
+        let inner = node_hash(
+            &leaf_hash(&entries[0].0, entries[0].1),
+            &leaf_hash(&entries[1].0, entries[1].1),
+        );
+        assert_eq!(tree.root(), inner);
+        assert_ne!(leaf_hash(&entries[0].0, entries[0].1), inner);
+    }
+
+    #[test]
+    fn parses_csv() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let csv = format!("address,allocation\n# presale\n\n{a}, 500\n{b},700\n");
+        let tree = AllowlistTree::from_csv(&csv).unwrap();
+        assert_eq!(tree.proof(&a).unwrap().allocation, 500);
+        assert_eq!(tree.proof(&b).unwrap().allocation, 700);
+        assert_eq!(
+            tree.root(),
+            AllowlistTree::new(vec![(a, 500), (b, 700)]).root()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_csv() {
+        let a = Pubkey::new_unique();
+        assert_eq!(
+            AllowlistTree::from_csv(&format!("{a}")).unwrap_err(),
+            AllowlistCsvError::MalformedLine(1)
+        );
+        assert_eq!(
+            AllowlistTree::from_csv("nope,1").unwrap_err(),
+            AllowlistCsvError::InvalidAddress(1)
+        );
+        assert_eq!(
+            AllowlistTree::from_csv(&format!("{a},-1")).unwrap_err(),
+            AllowlistCsvError::InvalidAllocation(1)
+        );
+        assert_eq!(
+            AllowlistTree::from_csv(&format!("{a},1\n{a},2")).unwrap_err(),
+            AllowlistCsvError::DuplicateAddress(2)
+        );
+        assert_eq!(
+            AllowlistTree::from_csv("address,allocation\n# none\n").unwrap_err(),
+            AllowlistCsvError::Empty
+        );
+    }
+}
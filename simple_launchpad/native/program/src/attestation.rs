@@ -0,0 +1,182 @@
+//! Attestations gating sales to verified buyers.
+//!
+//! The project's attestor signs `buyer || project || expiry` off-chain. The
+//! buyer submits an ed25519 program instruction verifying that signature
+//! ahead of `Mint` in the same transaction, and `Mint` finds it through the
+//! instructions sysvar. The runtime fails the whole transaction if the
+//! signature does not verify.
+
+use solana_program::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+/// Length of an attestation message.
+pub const MESSAGE_LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 8;
+
+const SIGNATURE_LEN: usize = 64;
+const HEADER_LEN: usize = 2;
+const OFFSETS_LEN: usize = 14;
+/// Instruction index meaning "this instruction" in ed25519 signature offsets.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Message the attestor signs to let `buyer` contribute to `project` until `expiry`.
+pub fn message(buyer: &Pubkey, project: &Pubkey, expiry: i64) -> [u8; MESSAGE_LEN] {
+    let mut message = [0; MESSAGE_LEN];
+    message[..32].copy_from_slice(buyer.as_ref());
+    message[32..64].copy_from_slice(project.as_ref());
+    message[64..].copy_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// Returns the expiry of an attestation `message` for `buyer` and `project`,
+/// or `None` if it attests to someone or something else.
+pub fn expiry(message: &[u8], buyer: &Pubkey, project: &Pubkey) -> Option<i64> {
+    if message.len() != MESSAGE_LEN
+        || message[..32] != buyer.to_bytes()
+        || message[32..64] != project.to_bytes()
+    {
+        return None;
+    }
+    Some(i64::from_le_bytes(message[64..].try_into().ok()?))
+}
+
+/// Returns the `(signer, message)` pairs verified by an ed25519 program
+/// instruction with `data`. Signatures whose key or message live in another
+/// instruction are skipped, since the data read here is not what was verified.
+pub fn verified_messages(data: &[u8]) -> Vec<(Pubkey, &[u8])> {
+    let count = data.first().copied().unwrap_or_default() as usize;
+    (0..count)
+        .filter_map(|index| {
+            let start = HEADER_LEN + index * OFFSETS_LEN;
+            let offsets = data.get(start..start + OFFSETS_LEN)?;
+            let field = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+            if field(1) != CURRENT_INSTRUCTION
+                || field(3) != CURRENT_INSTRUCTION
+                || field(6) != CURRENT_INSTRUCTION
+            {
+                return None;
+            }
+            let public_key_offset = field(2) as usize;
+            let public_key = data.get(public_key_offset..public_key_offset + PUBKEY_BYTES)?;
+            let message_offset = field(4) as usize;
+            let message = data.get(message_offset..message_offset + field(5) as usize)?;
+            Some((Pubkey::try_from(public_key).ok()?, message))
+        })
+        .collect()
+}
+
+/// Creates the ed25519 program instruction verifying `attestor`'s `signature`
+/// over the attestation for (`buyer`, `project`, `expiry`). Place it before
+/// the `Mint` instruction it gates.
+pub fn verify_attestation(
+    attestor: &Pubkey,
+    signature: &[u8; SIGNATURE_LEN],
+    buyer: &Pubkey,
+    project: &Pubkey,
+    expiry: i64,
+) -> Instruction {
+    let public_key_offset = HEADER_LEN + OFFSETS_LEN;
+    let signature_offset = public_key_offset + PUBKEY_BYTES;
+    let message_offset = signature_offset + SIGNATURE_LEN;
+
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset as u16,
+        CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        CURRENT_INSTRUCTION,
+        message_offset as u16,
+        MESSAGE_LEN as u16,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(attestor.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(&message(buyer, project, expiry));
+    Instruction::new_with_bytes(ed25519_program::ID, &data, vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attestation_data(attestor: &Pubkey, buyer: &Pubkey, project: &Pubkey) -> Vec<u8> {
+        verify_attestation(attestor, &[7; SIGNATURE_LEN], buyer, project, 1_700_000_000).data
+    }
+
+    /// Sets offsets field `i` of the first signature to `value`.
+    fn set_field(data: &mut [u8], i: usize, value: u16) {
+        let at = HEADER_LEN + 2 * i;
+        data[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn verified_messages_round_trips_an_attestation() {
+        let (attestor, buyer, project) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let data = attestation_data(&attestor, &buyer, &project);
+
+        let verified = verified_messages(&data);
+        assert_eq!(verified.len(), 1);
+        let (signer, message) = verified[0];
+        assert_eq!(signer, attestor);
+        assert_eq!(expiry(message, &buyer, &project), Some(1_700_000_000));
+    }
+
+    #[test]
+    fn verified_messages_skips_data_in_another_instruction() {
+        let (attestor, buyer, project) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        for i in [1, 3, 6] {
+            let mut data = attestation_data(&attestor, &buyer, &project);
+            set_field(&mut data, i, 0);
+            assert!(verified_messages(&data).is_empty(), "field {}", i);
+        }
+    }
+
+    #[test]
+    fn verified_messages_skips_out_of_bounds_offsets() {
+        let (attestor, buyer, project) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let data = attestation_data(&attestor, &buyer, &project);
+        assert!(verified_messages(&data[..data.len() - 1]).is_empty());
+
+        let mut data = attestation_data(&attestor, &buyer, &project);
+        set_field(&mut data, 2, u16::MAX - 1);
+        assert!(verified_messages(&data).is_empty());
+
+        let mut data = attestation_data(&attestor, &buyer, &project);
+        set_field(&mut data, 5, u16::MAX);
+        assert!(verified_messages(&data).is_empty());
+    }
+
+    #[test]
+    fn verified_messages_skips_missing_offsets() {
+        assert!(verified_messages(&[]).is_empty());
+        assert!(verified_messages(&[3, 0]).is_empty());
+        assert!(verified_messages(&[1, 0, 0xff, 0xff]).is_empty());
+    }
+
+    #[test]
+    fn expiry_rejects_other_attestations() {
+        let (buyer, project) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let message = message(&buyer, &project, -5);
+        assert_eq!(expiry(&message, &buyer, &project), Some(-5));
+        assert_eq!(expiry(&message, &project, &buyer), None);
+        assert_eq!(expiry(&message, &Pubkey::new_unique(), &project), None);
+        assert_eq!(expiry(&message, &buyer, &Pubkey::new_unique()), None);
+        assert_eq!(expiry(&message[..MESSAGE_LEN - 1], &buyer, &project), None);
+    }
+}
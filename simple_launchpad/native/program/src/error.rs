@@ -27,6 +27,90 @@ pub enum LaunchTokenError {
     PermissionForbidden,
     #[error("Mint account not exist")]
     MintAccountNotExist,
+    #[error("Invalid launch time range")]
+    InvalidTimeRange,
+    #[error("Sale already started")]
+    SaleAlreadyStarted,
+    #[error("Claim expired")]
+    ClaimExpired,
+    #[error("Claim not end")]
+    ClaimNotEnded,
+    #[error("Launch not end")]
+    LaunchNotEnded,
+    #[error("No proceeds to withdraw")]
+    NoProceedsToWithdraw,
+    #[error("Invalid soft cap or hard cap")]
+    InvalidCaps,
+    #[error("Hard cap exceeded")]
+    HardCapExceeded,
+    #[error("Sale not successful")]
+    SaleNotSuccessful,
+    #[error("Refund not available")]
+    RefundNotAvailable,
+    #[error("Invalid contribution limits")]
+    InvalidContributionLimits,
+    #[error("Below minimum contribution")]
+    BelowMinContribution,
+    #[error("Exceeds maximum contribution per wallet")]
+    ExceedsMaxContribution,
+    #[error("Invalid allowlist window")]
+    InvalidAllowlistWindow,
+    #[error("Buyer not in allowlist")]
+    NotAllowlisted,
+    #[error("Exceeds allowlist allocation")]
+    ExceedsAllowlistAllocation,
+    #[error("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[error("Invalid PDA")]
+    InvalidPda,
+    #[error("Invalid token price")]
+    InvalidTokenPrice,
+    #[error("Incorrect token mint")]
+    IncorrectTokenMint,
+    #[error("Incorrect token vault")]
+    IncorrectTokenVault,
+    #[error("Insufficient sale tokens")]
+    InsufficientSaleTokens,
+    #[error("No unsold tokens")]
+    NoUnsoldTokens,
+    #[error("Incorrect payment mint")]
+    IncorrectPaymentMint,
+    #[error("Incorrect payment escrow")]
+    IncorrectPaymentEscrow,
+    #[error("Sale paused")]
+    SalePaused,
+    #[error("Sale cancelled")]
+    SaleCancelled,
+    #[error("Invalid multisig")]
+    InvalidMultisig,
+    #[error("No pending manager")]
+    NoPendingManager,
+    #[error("Incorrect treasury")]
+    IncorrectTreasury,
+    #[error("Invalid platform fee")]
+    InvalidPlatformFee,
+    #[error("Obligations outstanding")]
+    ObligationsOutstanding,
+    #[error("Incorrect creator")]
+    IncorrectCreator,
+    #[error("Invalid auction")]
+    InvalidAuction,
+    #[error("Invalid rounds")]
+    InvalidRounds,
+    #[error("No active round")]
+    NoActiveRound,
+    #[error("Round cap exceeded")]
+    RoundCapExceeded,
+    #[error("Invalid referral rate")]
+    InvalidReferralRate,
+    #[error("Invalid referrer")]
+    InvalidReferrer,
+    #[error("No referral rewards")]
+    NoReferralRewards,
+    #[error("Attestation required")]
+    AttestationRequired,
+    #[error("Attestation expired")]
+    AttestationExpired,
 }
 impl From<LaunchTokenError> for ProgramError {
     fn from(e: LaunchTokenError) -> Self {
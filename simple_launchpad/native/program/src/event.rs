@@ -0,0 +1,209 @@
+//! Structured events logged by the program.
+//!
+//! Each event is Borsh-serialized as a `LaunchpadEvent` and logged with
+//! `sol_log_data`, which shows up in transaction logs as a base64
+//! `Program data: ` line. The variant index is the leading byte, so indexers
+//! can tell events apart without parsing `msg!` output.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+/// A buyer contributed to a sale.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ContributionEvent {
+    pub project: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    /// Buyer's total contribution after this one.
+    pub total_contributed: u64,
+    /// Project's total raised after this contribution.
+    pub total_raised: u64,
+}
+
+/// A buyer claimed vested tokens.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ClaimEvent {
+    pub project: Pubkey,
+    pub buyer: Pubkey,
+    /// Tokens released by this claim, net of any transfer fee.
+    pub amount: u64,
+    /// Tokens released to the buyer so far.
+    pub claimed_amount: u64,
+}
+
+/// A buyer recovered their contribution from a failed or cancelled sale, or the
+/// excess of their contribution to an oversubscribed one.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RefundEvent {
+    pub project: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+/// A referrer claimed the referral rewards earned on a successful sale.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ReferralRewardEvent {
+    pub project: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+/// A manager created a project.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ProjectCreatedEvent {
+    pub project: Pubkey,
+    pub manager: Pubkey,
+    pub project_id: u64,
+    pub token_mint: Pubkey,
+    pub payment_mint: Option<Pubkey>,
+    pub launch_start_time: u64,
+    pub launch_end_time: u64,
+}
+
+/// Events logged by the program.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub enum LaunchpadEvent {
+    Contribution(ContributionEvent),
+    Claim(ClaimEvent),
+    Refund(RefundEvent),
+    ProjectCreated(ProjectCreatedEvent),
+    ReferralReward(ReferralRewardEvent),
+}
+
+impl LaunchpadEvent {
+    /// Logs the event with `sol_log_data`.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+pub use self::decode::*;
+
+#[cfg(not(target_os = "solana"))]
+mod decode {
+    use super::LaunchpadEvent;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use borsh::BorshDeserialize;
+    use solana_program::pubkey::Pubkey;
+
+    const DATA_PREFIX: &str = "Program data: ";
+
+    impl LaunchpadEvent {
+        /// Decodes a `Program data: ` log line, returning `None` for other lines
+        /// and for data that is not a launchpad event.
+        pub fn from_log(line: &str) -> Option<Self> {
+            let data = line.strip_prefix(DATA_PREFIX)?;
+            let bytes = STANDARD.decode(data.trim()).ok()?;
+            Self::try_from_slice(&bytes).ok()
+        }
+    }
+
+    /// Decodes the events `program_id` logged in a transaction's log messages.
+    ///
+    /// Follows the `Program <id> invoke` / `success` / `failed` lines so that
+    /// data logged by other programs, including ones invoked through CPI, is
+    /// skipped.
+    pub fn decode_events<'a>(
+        program_id: &Pubkey,
+        logs: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<LaunchpadEvent> {
+        let program_id = program_id.to_string();
+        let mut invocations: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+        for line in logs {
+            if let Some(rest) = line.strip_prefix("Program ") {
+                let words: Vec<&str> = rest.split_whitespace().collect();
+                match words.as_slice() {
+                    [id, "invoke", depth] if !id.ends_with(':') && depth.starts_with('[') => {
+                        invocations.push(id);
+                        continue;
+                    }
+                    [id, "success"] | [id, "failed:", ..] if !id.ends_with(':') => {
+                        invocations.pop();
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if invocations.last() == Some(&program_id.as_str()) {
+                events.extend(LaunchpadEvent::from_log(line));
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    fn refund(amount: u64) -> LaunchpadEvent {
+        LaunchpadEvent::Refund(RefundEvent {
+            project: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            amount,
+        })
+    }
+
+    fn data_line(event: &LaunchpadEvent) -> String {
+        format!(
+            "Program data: {}",
+            STANDARD.encode(event.try_to_vec().unwrap())
+        )
+    }
+
+    #[test]
+    fn from_log_round_trips() {
+        let event = refund(42);
+        assert_eq!(LaunchpadEvent::from_log(&data_line(&event)), Some(event));
+        assert_eq!(LaunchpadEvent::from_log("Program log: Refund"), None);
+        assert_eq!(LaunchpadEvent::from_log("Program data: not base64!"), None);
+        assert_eq!(
+            LaunchpadEvent::from_log(&format!("Program data: {}", STANDARD.encode([0xff]))),
+            None
+        );
+    }
+
+    #[test]
+    fn decode_events_keeps_only_the_program_own_data() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let (first, nested, second, outside) = (refund(1), refund(2), refund(3), refund(4));
+        let logs = [
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: Claim".to_string(),
+            data_line(&first),
+            format!("Program {} invoke [2]", other),
+            data_line(&nested),
+            format!("Program {} success", other),
+            data_line(&second),
+            format!(
+                "Program {} consumed 5000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+            data_line(&outside),
+        ];
+        let events = decode_events(&program_id, logs.iter().map(String::as_str));
+        assert_eq!(events, vec![first, second]);
+    }
+
+    #[test]
+    fn decode_events_follows_failed_invocations() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let (nested, ours) = (refund(1), refund(2));
+        let logs = [
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", other),
+            data_line(&nested),
+            format!("Program {} failed: custom program error: 0x1", other),
+            data_line(&ours),
+            format!("Program {} success", program_id),
+        ];
+        let events = decode_events(&program_id, logs.iter().map(String::as_str));
+        assert_eq!(events, vec![ours]);
+    }
+}
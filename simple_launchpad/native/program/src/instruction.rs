@@ -1,14 +1,1115 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::state::{ProjectInfo, SaleRound};
+use crate::{
+    find_claim_authority_address, find_config_address, find_mint_info_address,
+    find_multisig_address, find_project_address, find_referral_address, find_vault_address,
+    get_payment_escrow_address, get_token_vault_address,
+};
+
+/// Instructions supported by the launchpad program.
+///
+/// Borsh encodes the variant index as the leading byte, so every instruction
+/// carries an explicit discriminant regardless of its payload length.
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
-pub struct Mint {
-    pub amount: u64,
-    pub bump: u8,
+pub enum LaunchpadInstruction {
+    /// Contribute to the sale, creating the buyer's mint account on first use.
+    ///
+    /// Lamports are escrowed in the project vault, or payment tokens in the
+    /// vault's payment escrow, until the seller withdraws them. During the
+    /// allowlist phase, or in a round with an allowlist, `allowlist` must prove
+    /// the buyer's allocation. In a Dutch auction the contribution bids for
+    /// tokens at the current price. In a multi-round sale it is attributed to
+    /// the round open at the time and priced at that round's price. A
+    /// `referrer` other than the buyer accrues the project's referral rate on
+    /// the contribution, unless the reward is zero. If the project has an attestor, an earlier ed25519
+    /// program instruction in the transaction must verify its unexpired
+    /// attestation for the buyer; see `attestation::verify_attestation`.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Buyer
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Mint info PDA for (project, buyer)
+    /// 3. `[writable]` Project account
+    /// 4. `[writable]` Project vault PDA
+    /// 5. `[]` System program
+    ///
+    /// For sales paid in SPL tokens:
+    /// 6. `[writable]` Buyer's payment token account
+    /// 7. `[writable]` Payment escrow
+    /// 8. `[]` Token program
+    ///
+    /// Followed by `[writable]` the referral PDA for (project, referrer) when
+    /// `referrer` is set, then `[]` the instructions sysvar when the project
+    /// has an attestor.
+    Mint {
+        amount: u64,
+        allowlist: Option<AllowlistProof>,
+        referrer: Option<Pubkey>,
+    },
+
+    /// Create the claim authority PDA that owns the sale tokens.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer
+    /// 1. `[writable]` Claim authority PDA for the project
+    /// 2. `[]` Project account
+    /// 3. `[]` System program
+    CreateClaimAccount,
+
+    /// Claim the currently vested part of the purchased tokens once the claim
+    /// period has started. May be called repeatedly as more tokens vest; the
+    /// claim that releases the last tokens closes the buyer's mint info account.
+    ///
+    /// In an oversubscribed overflow sale the buyer's allocation is scaled down
+    /// pro rata and the first claim also refunds the excess contribution. In a
+    /// Dutch auction the tokens bid for are settled at the clearing price and
+    /// the first claim refunds the difference to what the buyer paid.
+    ///
+    /// For Token-2022 mints with a transfer fee the vault sends enough for the
+    /// buyer to receive the vested amount net of the fee. The fees paid for a
+    /// buyer are capped at the fee on their whole allocation, so claiming in
+    /// many parts may cost a base unit of rounding per claim.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Buyer
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Mint info PDA for (project, buyer)
+    /// 3. `[]` Sale token mint stored on the project
+    /// 4. `[writable]` Project account
+    /// 5. `[]` Claim authority PDA
+    /// 6. `[writable]` Project token vault
+    /// 7. `[writable]` Buyer's associated token account
+    /// 8. `[]` Token program
+    /// 9. `[]` System program
+    /// 10. `[]` Associated token account program
+    /// 11. `[writable]` Project vault PDA
+    ///
+    /// For sales paid in SPL tokens:
+    /// 12. `[writable]` Payment escrow
+    /// 13. `[writable]` Buyer's payment token account
+    /// 14. `[]` Token program of the payment mint
+    Claim,
+
+    /// Create the project PDA for a new sale and store its configuration.
+    ///
+    /// The PDA is seeded by the manager and `project_id`, so one manager can
+    /// run several sales side by side. Requires
+    /// `launch_start_time < launch_end_time <= claim_start_time`; a non-zero
+    /// `claim_end_time` must come after `claim_start_time`. The current
+    /// platform fee is fixed for the project at creation, and `referral_bps`
+    /// may not exceed what it leaves of the proceeds. With `overflow` set
+    /// the sale keeps accepting past `hard_cap` and allocates pro rata. A
+    /// non-zero `auction_start_denominator` makes the sale a Dutch auction whose
+    /// price decays to the fixed price by `launch_end_time`. Non-empty `rounds`
+    /// split the launch window into rounds with their own price, cap and allowlist.
+    /// A non-default `attestor` restricts contributions to attested buyers.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Manager
+    /// 1. `[writable]` Project PDA
+    /// 2. `[writable]` Project vault PDA
+    /// 3. `[]` System program
+    /// 4. `[]` Config PDA
+    ///
+    /// For sales paid in SPL tokens:
+    /// 5. `[]` Payment mint
+    /// 6. `[writable]` Payment escrow, the vault's associated token account
+    /// 7. `[]` Token program
+    /// 8. `[]` Associated token account program
+    InitializeProject(InitializeProjectArgs),
+
+    /// Update the sale configuration. Only fields set to `Some` are changed.
+    ///
+    /// Once the sale has started only the attestor may still be changed, the
+    /// launch end time pushed back up to the claim start time, the claim start
+    /// time brought forward and the claim deadline extended, the times not into
+    /// the past; see `UpdateProjectArgs::changes_economics`. No time may change
+    /// after the sale has ended.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[signer]` Multisig signers, when the manager is a multisig
+    UpdateProject(UpdateProjectArgs),
+
+    /// Return the tokens left unclaimed after `claim_end_time` to the seller.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[]` Clock sysvar
+    /// 2. `[]` Project account
+    /// 3. `[]` Claim authority PDA
+    /// 4. `[writable]` Project token vault
+    /// 5. `[writable]` Seller's token account
+    /// 6. `[]` Sale token mint
+    /// 7. `[]` Token program
+    /// 8. `[signer]` Multisig signers, when the manager is a multisig
+    SweepUnclaimed,
+
+    /// Move the escrowed contributions to the seller once the sale has ended
+    /// at or above its soft cap, less the platform fee sent to the treasury.
+    /// Excess contributions of an oversubscribed sale stay in escrow until
+    /// their buyers claim, or their allocations are settled with `Refund`
+    /// after the claim deadline.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[writable]` Project vault PDA
+    /// 4. `[]` Config PDA
+    /// 5. `[writable]` Treasury
+    ///
+    /// For sales paid in SPL tokens:
+    /// 6. `[writable]` Payment escrow
+    /// 7. `[writable]` Seller's payment token account
+    /// 8. `[writable]` Treasury's payment token account
+    /// 9. `[]` Token program
+    WithdrawProceeds,
+
+    /// Return the buyer's escrowed contribution after a sale that ended below
+    /// its soft cap, closing the buyer's mint info account. Buyers who claimed
+    /// tokens cannot refund, and excess already refunded is deducted.
+    ///
+    /// After the `claim_end_time` of a successful sale, settles the buyer's
+    /// allocation instead: the excess contribution not yet refunded and the
+    /// mint info rent go back to the buyer. Anyone may send it then, so
+    /// allocations left unclaimed do not keep `CloseProject` from succeeding.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Buyer; need not sign when settling
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Mint info PDA for (project, buyer)
+    /// 3. `[writable]` Project account
+    /// 4. `[writable]` Project vault PDA
+    ///
+    /// For sales paid in SPL tokens:
+    /// 5. `[writable]` Payment escrow
+    /// 6. `[writable]` Buyer's payment token account
+    /// 7. `[]` Token program
+    Refund,
+
+    /// Move `amount` sale tokens from the seller into the project token vault,
+    /// the claim authority's associated token account, creating it if needed.
+    /// Only the amount the vault receives after any transfer fee is credited,
+    /// and contributions are only accepted while the deposit covers the tokens
+    /// owed plus the transfer fees withheld when they are claimed.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[]` Claim authority PDA
+    /// 4. `[writable]` Seller's token account
+    /// 5. `[writable]` Project token vault
+    /// 6. `[]` Sale token mint
+    /// 7. `[]` Token program
+    /// 8. `[]` System program
+    /// 9. `[]` Associated token account program
+    DepositSaleTokens { amount: u64 },
+
+    /// Return the deposited tokens not owed to buyers to the seller once the
+    /// sale has ended. All of them are unsold if the sale missed its soft cap.
+    /// The transfer fees on the tokens still to be claimed, if any, stay in the
+    /// vault until every buyer has claimed in full, after which the whole
+    /// balance is returned.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Seller
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[]` Claim authority PDA
+    /// 4. `[writable]` Project token vault
+    /// 5. `[writable]` Seller's token account
+    /// 6. `[]` Sale token mint
+    /// 7. `[]` Token program
+    ReclaimUnsoldTokens,
+
+    /// Halt `Mint` and `Claim` until the manager unpauses the project.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[writable]` Project account
+    /// 2. `[signer]` Multisig signers, when the manager is a multisig
+    Pause,
+
+    /// Resume a paused project.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[writable]` Project account
+    /// 2. `[signer]` Multisig signers, when the manager is a multisig
+    Unpause,
+
+    /// Permanently cancel the sale. Buyers may then `Refund` their contributions
+    /// and the seller may reclaim every deposited token. Not allowed once a
+    /// successful sale has ended.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[signer]` Multisig signers, when the manager is a multisig
+    CancelSale,
+
+    /// Propose `new_manager` to take over the project; it must then call
+    /// `AcceptManager`. Proposing the default pubkey withdraws the proposal.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[writable]` Project account
+    /// 2. `[signer]` Multisig signers, when the manager is a multisig
+    ProposeManager { new_manager: Pubkey },
+
+    /// Become the manager of the project as its pending manager.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Pending manager, or its multisig account
+    /// 1. `[writable]` Project account
+    /// 2. `[signer]` Multisig signers, when the pending manager is a multisig
+    AcceptManager,
+
+    /// Create a multisig account that can be set as a project manager. Every
+    /// manager instruction then needs `m` of `signers` to sign.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer
+    /// 1. `[writable]` Multisig PDA for (payer, multisig_id)
+    /// 2. `[]` System program
+    CreateMultisig {
+        multisig_id: u64,
+        m: u8,
+        signers: Vec<Pubkey>,
+    },
+
+    /// Create the platform config. Only the program's upgrade authority may,
+    /// and it becomes the admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Admin, the program's upgrade authority
+    /// 1. `[writable]` Config PDA
+    /// 2. `[]` System program
+    /// 3. `[]` ProgramData account of this program
+    InitializeConfig {
+        treasury: Pubkey,
+        platform_fee_bps: u16,
+    },
+
+    /// Change the platform config. Only fields set to `Some` are changed; the
+    /// fee of existing projects is unaffected.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Admin, or its multisig account
+    /// 1. `[writable]` Config PDA
+    /// 2. `[signer]` Multisig signers, when the admin is a multisig
+    UpdateConfig {
+        admin: Option<Pubkey>,
+        treasury: Option<Pubkey>,
+        platform_fee_bps: Option<u16>,
+    },
+
+    /// Close the project once the sale has ended or been cancelled and nothing
+    /// is owed: every buyer has claimed in full, refunded or been settled with
+    /// `Refund` after the claim deadline, the proceeds have been withdrawn and
+    /// the token vault is empty. Transfer fees withheld in the token vault are
+    /// first harvested to the sale token mint. The token vault, payment escrow,
+    /// claim authority, vault and project accounts are closed and their rent
+    /// returned to the project's creator.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Manager, or its multisig account
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Project account
+    /// 3. `[writable]` Project vault PDA
+    /// 4. `[writable]` Claim authority PDA
+    /// 5. `[writable]` Project token vault
+    /// 6. `[]` Token program of the sale token
+    /// 7. `[writable]` Creator of the project
+    /// 8. `[writable]` Sale token mint
+    ///
+    /// For sales paid in SPL tokens:
+    /// 9. `[writable]` Payment escrow
+    /// 10. `[]` Token program of the payment mint
+    ///
+    /// Followed by `[signer]` multisig signers, when the manager is a multisig.
+    CloseProject,
+
+    /// Pay the referrer the rewards accrued on a sale that ended at or above
+    /// its soft cap, out of the escrowed contributions, and close the referral
+    /// account. Rewards are scaled down like the contributions when part of
+    /// them is refunded as excess. After a sale that failed or was cancelled,
+    /// or when the rewards round down to nothing, the account is closed
+    /// without a payout.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Referrer
+    /// 1. `[]` Clock sysvar
+    /// 2. `[writable]` Referral PDA for (project, referrer)
+    /// 3. `[writable]` Project account
+    /// 4. `[writable]` Project vault PDA
+    ///
+    /// For sales paid in SPL tokens:
+    /// 5. `[writable]` Payment escrow
+    /// 6. `[writable]` Referrer's payment token account
+    /// 7. `[]` Token program
+    ClaimReferralRewards,
 }
 
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
-pub struct Claim {}
+/// Merkle proof that a buyer may contribute up to `allocation` lamports during
+/// the allowlist phase, or during a round with an allowlist.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct AllowlistProof {
+    pub allocation: u64,
+    pub proof: Vec<[u8; 32]>,
+}
 
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
-pub struct CreateClaimAccount {
-    pub bump: u8,
+/// Sale configuration supplied to `InitializeProject`.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct InitializeProjectArgs {
+    pub project_id: u64,
+    pub token_price_numerator: u64,
+    pub token_price_denominator: u64,
+    pub seller_account: Pubkey,
+    pub launch_start_time: u64,
+    pub launch_end_time: u64,
+    pub claim_start_time: u64,
+    pub claim_end_time: u64,
+    pub token_program_id: Pubkey,
+    pub token_mint: Pubkey,
+    pub soft_cap: u64,
+    pub hard_cap: u64,
+    pub min_contribution: u64,
+    pub max_contribution_per_wallet: u64,
+    pub allowlist_root: [u8; 32],
+    pub allowlist_start_time: u64,
+    pub allowlist_end_time: u64,
+    pub tge_unlock_bps: u16,
+    pub vesting_cliff: u64,
+    pub vesting_duration: u64,
+    pub payment_mint: Option<Pubkey>,
+    pub overflow: bool,
+    pub auction_start_denominator: u64,
+    pub rounds: Vec<SaleRound>,
+    pub referral_bps: u16,
+    pub attestor: Pubkey,
+}
+
+/// Fields changed by `UpdateProject`; `None` leaves the stored value as is.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct UpdateProjectArgs {
+    pub token_price_numerator: Option<u64>,
+    pub token_price_denominator: Option<u64>,
+    pub seller_account: Option<Pubkey>,
+    pub launch_start_time: Option<u64>,
+    pub launch_end_time: Option<u64>,
+    pub claim_start_time: Option<u64>,
+    pub claim_end_time: Option<u64>,
+    pub soft_cap: Option<u64>,
+    pub hard_cap: Option<u64>,
+    pub min_contribution: Option<u64>,
+    pub max_contribution_per_wallet: Option<u64>,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub allowlist_start_time: Option<u64>,
+    pub allowlist_end_time: Option<u64>,
+    pub tge_unlock_bps: Option<u16>,
+    pub vesting_cliff: Option<u64>,
+    pub vesting_duration: Option<u64>,
+    pub overflow: Option<bool>,
+    pub auction_start_denominator: Option<u64>,
+    pub rounds: Option<Vec<SaleRound>>,
+    pub referral_bps: Option<u16>,
+    pub attestor: Option<Pubkey>,
+}
+
+impl UpdateProjectArgs {
+    /// Returns whether the update touches fields that are locked once the sale
+    /// starts. Extending the sale or the claim deadline and bringing the claim
+    /// start forward, relative to `project_info`, are not locked.
+    pub fn changes_economics(&self, project_info: &ProjectInfo) -> bool {
+        self.token_price_numerator.is_some()
+            || self.token_price_denominator.is_some()
+            || self.seller_account.is_some()
+            || self.launch_start_time.is_some()
+            || self
+                .launch_end_time
+                .is_some_and(|launch_end_time| launch_end_time < project_info.launch_end_time)
+            || self
+                .claim_start_time
+                .is_some_and(|claim_start_time| claim_start_time > project_info.claim_start_time)
+            || self.claim_end_time.is_some_and(|claim_end_time| {
+                project_info.claim_end_time == 0
+                    || (claim_end_time != 0 && claim_end_time < project_info.claim_end_time)
+            })
+            || self.soft_cap.is_some()
+            || self.hard_cap.is_some()
+            || self.min_contribution.is_some()
+            || self.max_contribution_per_wallet.is_some()
+            || self.allowlist_root.is_some()
+            || self.allowlist_start_time.is_some()
+            || self.allowlist_end_time.is_some()
+            || self.tge_unlock_bps.is_some()
+            || self.vesting_cliff.is_some()
+            || self.vesting_duration.is_some()
+            || self.overflow.is_some()
+            || self.auction_start_denominator.is_some()
+            || self.rounds.is_some()
+            || self.referral_bps.is_some()
+    }
+
+    /// Returns whether the update moves any of the sale or claim times.
+    pub fn changes_times(&self) -> bool {
+        self.launch_start_time.is_some()
+            || self.launch_end_time.is_some()
+            || self.claim_start_time.is_some()
+            || self.claim_end_time.is_some()
+            || self.allowlist_start_time.is_some()
+            || self.allowlist_end_time.is_some()
+    }
+}
+
+/// Payment mint of a sale paid in SPL tokens and the token program owning it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaymentToken {
+    pub mint: Pubkey,
+    pub token_program_id: Pubkey,
+}
+
+impl PaymentToken {
+    /// Returns `owner`'s associated token account for the payment mint and
+    /// the project's payment escrow.
+    fn token_accounts(
+        &self,
+        program_id: &Pubkey,
+        project: &Pubkey,
+        owner: &Pubkey,
+    ) -> (Pubkey, Pubkey) {
+        let owner_token_account =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                owner,
+                &self.mint,
+                &self.token_program_id,
+            );
+        let escrow =
+            get_payment_escrow_address(program_id, project, &self.mint, &self.token_program_id);
+        (owner_token_account, escrow)
+    }
+}
+
+impl LaunchpadInstruction {
+    /// Serializes the instruction into its tagged byte representation.
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+}
+
+/// Creates a `Mint` instruction.
+///
+/// The instructions sysvar is always appended, so attested buyers only need
+/// to add the `attestation::verify_attestation` instruction before it.
+pub fn mint(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    project: &Pubkey,
+    amount: u64,
+    allowlist: Option<AllowlistProof>,
+    payment: Option<&PaymentToken>,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let (mint_info, _) = find_mint_info_address(program_id, project, buyer);
+    let (vault, _) = find_vault_address(program_id, project);
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(mint_info, false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(payment) = payment {
+        let (buyer_token_account, escrow) = payment.token_accounts(program_id, project, buyer);
+        accounts.extend([
+            AccountMeta::new(buyer_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+        ]);
+    }
+    if let Some(referrer) = referrer {
+        let (referral, _) = find_referral_address(program_id, project, referrer);
+        accounts.push(AccountMeta::new(referral, false));
+    }
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::Mint {
+            amount,
+            allowlist,
+            referrer: referrer.copied(),
+        }
+        .pack(),
+        accounts,
+    )
+}
+
+/// Creates a `CreateClaimAccount` instruction.
+pub fn create_claim_account(program_id: &Pubkey, payer: &Pubkey, project: &Pubkey) -> Instruction {
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::CreateClaimAccount.pack(),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(claim_authority, false),
+            AccountMeta::new_readonly(*project, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `Claim` instruction.
+pub fn claim(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    token_mint: &Pubkey,
+    project: &Pubkey,
+    token_program_id: &Pubkey,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let (mint_info, _) = find_mint_info_address(program_id, project, buyer);
+    let (vault, _) = find_vault_address(program_id, project);
+    let token_vault = get_token_vault_address(program_id, project, token_mint, token_program_id);
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    let buyer_token_account =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            buyer,
+            token_mint,
+            token_program_id,
+        );
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(mint_info, false),
+        AccountMeta::new_readonly(*token_mint, false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new_readonly(claim_authority, false),
+        AccountMeta::new(token_vault, false),
+        AccountMeta::new(buyer_token_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new(vault, false),
+    ];
+    if let Some(payment) = payment {
+        let (buyer_payment_account, escrow) = payment.token_accounts(program_id, project, buyer);
+        accounts.extend([
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(buyer_payment_account, false),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+        ]);
+    }
+    Instruction::new_with_bytes(*program_id, &LaunchpadInstruction::Claim.pack(), accounts)
+}
+
+/// Creates an `InitializeProject` instruction for the manager's project PDA.
+///
+/// `args.payment_mint` is taken from `payment`.
+pub fn initialize_project(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    mut args: InitializeProjectArgs,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let (project, _) = find_project_address(program_id, manager, args.project_id);
+    let (vault, _) = find_vault_address(program_id, &project);
+    let (config, _) = find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*manager, true),
+        AccountMeta::new(project, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(config, false),
+    ];
+    args.payment_mint = payment.map(|payment| payment.mint);
+    if let Some(payment) = payment {
+        accounts.extend([
+            AccountMeta::new_readonly(payment.mint, false),
+            AccountMeta::new(
+                get_payment_escrow_address(
+                    program_id,
+                    &project,
+                    &payment.mint,
+                    &payment.token_program_id,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ]);
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::InitializeProject(args).pack(),
+        accounts,
+    )
+}
+
+/// Creates an `UpdateProject` instruction.
+pub fn update_project(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    args: UpdateProjectArgs,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::UpdateProject(args).pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `SweepUnclaimed` instruction.
+pub fn sweep_unclaimed(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    token_mint: &Pubkey,
+    seller_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    let token_vault = get_token_vault_address(program_id, project, token_mint, token_program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::SweepUnclaimed.pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(*project, false),
+                AccountMeta::new_readonly(claim_authority, false),
+                AccountMeta::new(token_vault, false),
+                AccountMeta::new(*seller_token_account, false),
+                AccountMeta::new_readonly(*token_mint, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `WithdrawProceeds` instruction.
+pub fn withdraw_proceeds(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    project: &Pubkey,
+    treasury: &Pubkey,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, project);
+    let (config, _) = find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*treasury, false),
+    ];
+    if let Some(payment) = payment {
+        let (seller_token_account, escrow) = payment.token_accounts(program_id, project, seller);
+        let (treasury_token_account, _) = payment.token_accounts(program_id, project, treasury);
+        accounts.extend([
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(seller_token_account, false),
+            AccountMeta::new(treasury_token_account, false),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+        ]);
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::WithdrawProceeds.pack(),
+        accounts,
+    )
+}
+
+/// Creates a `Refund` instruction.
+pub fn refund(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    project: &Pubkey,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let (mint_info, _) = find_mint_info_address(program_id, project, buyer);
+    let (vault, _) = find_vault_address(program_id, project);
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(mint_info, false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new(vault, false),
+    ];
+    if let Some(payment) = payment {
+        let (buyer_token_account, escrow) = payment.token_accounts(program_id, project, buyer);
+        accounts.extend([
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(buyer_token_account, false),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+        ]);
+    }
+    Instruction::new_with_bytes(*program_id, &LaunchpadInstruction::Refund.pack(), accounts)
+}
+
+/// Creates a `Refund` instruction settling `buyer`'s allocation after the
+/// claim deadline, without the buyer's signature.
+pub fn settle_allocation(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    project: &Pubkey,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let mut instruction = refund(program_id, buyer, project, payment);
+    instruction.accounts[0].is_signer = false;
+    instruction
+}
+
+/// Creates a `DepositSaleTokens` instruction.
+pub fn deposit_sale_tokens(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    project: &Pubkey,
+    seller_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    let token_vault = get_token_vault_address(program_id, project, token_mint, token_program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::DepositSaleTokens { amount }.pack(),
+        vec![
+            AccountMeta::new(*seller, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new(*project, false),
+            AccountMeta::new_readonly(claim_authority, false),
+            AccountMeta::new(*seller_token_account, false),
+            AccountMeta::new(token_vault, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    )
+}
+
+/// Creates a `ReclaimUnsoldTokens` instruction.
+pub fn reclaim_unsold_tokens(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    project: &Pubkey,
+    token_mint: &Pubkey,
+    seller_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    let token_vault = get_token_vault_address(program_id, project, token_mint, token_program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::ReclaimUnsoldTokens.pack(),
+        vec![
+            AccountMeta::new_readonly(*seller, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new(*project, false),
+            AccountMeta::new_readonly(claim_authority, false),
+            AccountMeta::new(token_vault, false),
+            AccountMeta::new(*seller_token_account, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+    )
+}
+
+/// Creates a `Pause` instruction.
+pub fn pause(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::Pause.pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates an `Unpause` instruction.
+pub fn unpause(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::Unpause.pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `CancelSale` instruction.
+pub fn cancel_sale(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::CancelSale.pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `ProposeManager` instruction.
+pub fn propose_manager(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    new_manager: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::ProposeManager {
+            new_manager: *new_manager,
+        }
+        .pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*manager, signers.is_empty()),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates an `AcceptManager` instruction.
+pub fn accept_manager(
+    program_id: &Pubkey,
+    pending_manager: &Pubkey,
+    project: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::AcceptManager.pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*pending_manager, signers.is_empty()),
+                AccountMeta::new(*project, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `CreateMultisig` instruction for the payer's `multisig_id`-th multisig.
+pub fn create_multisig(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    multisig_id: u64,
+    m: u8,
+    signers: &[Pubkey],
+) -> Instruction {
+    let (multisig, _) = find_multisig_address(program_id, payer, multisig_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::CreateMultisig {
+            multisig_id,
+            m,
+            signers: signers.to_vec(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Appends the multisig `signers` approving for an authority to `accounts`.
+fn with_signers(mut accounts: Vec<AccountMeta>, signers: &[&Pubkey]) -> Vec<AccountMeta> {
+    accounts.extend(
+        signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(**signer, true)),
+    );
+    accounts
+}
+
+/// Creates an `InitializeConfig` instruction.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    platform_fee_bps: u16,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::InitializeConfig {
+            treasury: *treasury,
+            platform_fee_bps,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_data, false),
+        ],
+    )
+}
+
+/// Creates an `UpdateConfig` instruction.
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    new_admin: Option<Pubkey>,
+    treasury: Option<Pubkey>,
+    platform_fee_bps: Option<u16>,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::UpdateConfig {
+            admin: new_admin,
+            treasury,
+            platform_fee_bps,
+        }
+        .pack(),
+        with_signers(
+            vec![
+                AccountMeta::new_readonly(*admin, signers.is_empty()),
+                AccountMeta::new(config, false),
+            ],
+            signers,
+        ),
+    )
+}
+
+/// Creates a `CloseProject` instruction for `project`, whose state is `project_info`.
+pub fn close_project(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project: &Pubkey,
+    project_info: &ProjectInfo,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, project);
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    let token_vault = get_token_vault_address(
+        program_id,
+        project,
+        &project_info.token_mint,
+        &project_info.token_program_id,
+    );
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*manager, signers.is_empty()),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(claim_authority, false),
+        AccountMeta::new(token_vault, false),
+        AccountMeta::new_readonly(project_info.token_program_id, false),
+        AccountMeta::new(project_info.creator, false),
+        AccountMeta::new(project_info.token_mint, false),
+    ];
+    if let Some(payment_mint) = project_info.payment_mint {
+        accounts.extend([
+            AccountMeta::new(
+                get_payment_escrow_address(program_id, project, &payment_mint, &spl_token::ID),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]);
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::CloseProject.pack(),
+        with_signers(accounts, signers),
+    )
+}
+
+/// Creates a `ClaimReferralRewards` instruction.
+pub fn claim_referral_rewards(
+    program_id: &Pubkey,
+    referrer: &Pubkey,
+    project: &Pubkey,
+    payment: Option<&PaymentToken>,
+) -> Instruction {
+    let (referral, _) = find_referral_address(program_id, project, referrer);
+    let (vault, _) = find_vault_address(program_id, project);
+    let mut accounts = vec![
+        AccountMeta::new(*referrer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(referral, false),
+        AccountMeta::new(*project, false),
+        AccountMeta::new(vault, false),
+    ];
+    if let Some(payment) = payment {
+        let (referrer_token_account, escrow) =
+            payment.token_accounts(program_id, project, referrer);
+        accounts.extend([
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(referrer_token_account, false),
+            AccountMeta::new_readonly(payment.token_program_id, false),
+        ]);
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &LaunchpadInstruction::ClaimReferralRewards.pack(),
+        accounts,
+    )
 }
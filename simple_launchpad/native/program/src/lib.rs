@@ -1,11 +1,134 @@
+#[cfg(not(feature = "no-entrypoint"))]
 use solana_program::entrypoint as solana_entrypoint;
 
+#[cfg(not(feature = "no-entrypoint"))]
 use crate::entrypoint::process_instruction;
 
+pub mod allowlist;
+pub mod attestation;
 pub mod entrypoint;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod processor;
 pub mod state;
 
+use solana_program::pubkey::Pubkey;
+
+/// Finds the PDA of the `project_id`-th project created by `manager`.
+pub fn find_project_address(
+    program_id: &Pubkey,
+    manager: &Pubkey,
+    project_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::ProjectInfo::SEED_PREFIX.as_bytes(),
+            manager.as_ref(),
+            &project_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Finds the PDA holding the platform-wide `Config`.
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[state::Config::SEED_PREFIX.as_bytes()], program_id)
+}
+
+/// Finds the PDA of the `multisig_id`-th multisig created by `creator`.
+pub fn find_multisig_address(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    multisig_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::Multisig::SEED_PREFIX.as_bytes(),
+            creator.as_ref(),
+            &multisig_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Finds the PDA recording `buyer`'s contribution to `project`.
+pub fn find_mint_info_address(
+    program_id: &Pubkey,
+    project: &Pubkey,
+    buyer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::MintInfo::SEED_PREFIX.as_bytes(),
+            project.as_ref(),
+            buyer.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Finds the PDA accruing `referrer`'s referral rewards from `project`.
+pub fn find_referral_address(
+    program_id: &Pubkey,
+    project: &Pubkey,
+    referrer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::Referral::SEED_PREFIX.as_bytes(),
+            project.as_ref(),
+            referrer.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Finds the program-owned PDA escrowing the contributions to `project`.
+pub fn find_vault_address(program_id: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[processor::VAULT_SEED_PREFIX.as_bytes(), project.as_ref()],
+        program_id,
+    )
+}
+
+/// Finds the PDA that signs sale token transfers for `project`.
+pub fn find_claim_authority_address(program_id: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[processor::CLAIM_SEED_PREFIX.as_bytes(), project.as_ref()],
+        program_id,
+    )
+}
+
+/// Returns the claim authority's token account holding the sale tokens of `project`.
+pub fn get_token_vault_address(
+    program_id: &Pubkey,
+    project: &Pubkey,
+    token_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Pubkey {
+    let (claim_authority, _) = find_claim_authority_address(program_id, project);
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        &claim_authority,
+        token_mint,
+        token_program_id,
+    )
+}
+
+/// Returns the vault's token account escrowing contributions paid in `payment_mint`.
+pub fn get_payment_escrow_address(
+    program_id: &Pubkey,
+    project: &Pubkey,
+    payment_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Pubkey {
+    let (vault, _) = find_vault_address(program_id, project);
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault,
+        payment_mint,
+        token_program_id,
+    )
+}
+
+#[cfg(not(feature = "no-entrypoint"))]
 solana_entrypoint!(process_instruction);
@@ -1,51 +1,185 @@
-use borsh::{BorshDeserialize, BorshSerialize};
+use crate::allowlist;
+use crate::attestation;
 use crate::error::*;
+use crate::event::{
+    ClaimEvent, ContributionEvent, LaunchpadEvent, ProjectCreatedEvent, ReferralRewardEvent,
+    RefundEvent,
+};
+use crate::instruction::{
+    AllowlistProof, InitializeProjectArgs, LaunchpadInstruction, UpdateProjectArgs,
+};
 /// Program state handler.
 use crate::state::*;
+use crate::{
+    find_claim_authority_address, find_config_address, find_mint_info_address,
+    find_multisig_address, find_project_address, find_referral_address, find_vault_address,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    ed25519_program,
     // declare_id,
     entrypoint::ProgramResult,
     msg,
     program::invoke,
     program::invoke_signed,
     program_error::ProgramError,
+    program_pack::Pack,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
-    sysvar::{clock::Clock, Sysvar},
+    sysvar::{clock::Clock, instructions, Sysvar},
 };
 use spl_associated_token_account::instruction as associated_token_account_instruction;
-use std::str::FromStr;
+use spl_token::state::Mint;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{self, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint as TokenMint},
+};
 
 const LOWEST_MINT_LAMPORTS: u64 = 1000;
-const CLAIM_SEED_PREFIX: &str = "octo-claim";
-const PROJECT_PROGRAM_ID: &str = "FP16xDjSoAcS4NYHNLvpgSFbgUvkNfHbSm8Fo3a9RgxG";
+pub const CLAIM_SEED_PREFIX: &str = "octo-claim";
+pub const VAULT_SEED_PREFIX: &str = "octo-vault";
 
 pub struct Processor {}
 impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = LaunchpadInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match instruction {
+            LaunchpadInstruction::Mint {
+                amount,
+                allowlist,
+                referrer,
+            } => {
+                msg!("Mint");
+                Self::create_mint_account(program_id, accounts, amount, allowlist, referrer)
+            }
+            LaunchpadInstruction::CreateClaimAccount => {
+                msg!("CreateClaimAccount");
+                Self::create_claim_account(program_id, accounts)
+            }
+            LaunchpadInstruction::Claim => {
+                msg!("Claim");
+                Self::process_claim(program_id, accounts)
+            }
+            LaunchpadInstruction::InitializeProject(args) => {
+                msg!("InitializeProject");
+                Self::initialize_project(program_id, accounts, args)
+            }
+            LaunchpadInstruction::UpdateProject(args) => {
+                msg!("UpdateProject");
+                Self::update_project(program_id, accounts, args)
+            }
+            LaunchpadInstruction::SweepUnclaimed => {
+                msg!("SweepUnclaimed");
+                Self::sweep_unclaimed(program_id, accounts)
+            }
+            LaunchpadInstruction::WithdrawProceeds => {
+                msg!("WithdrawProceeds");
+                Self::withdraw_proceeds(program_id, accounts)
+            }
+            LaunchpadInstruction::Refund => {
+                msg!("Refund");
+                Self::process_refund(program_id, accounts)
+            }
+            LaunchpadInstruction::DepositSaleTokens { amount } => {
+                msg!("DepositSaleTokens");
+                Self::deposit_sale_tokens(program_id, accounts, amount)
+            }
+            LaunchpadInstruction::ReclaimUnsoldTokens => {
+                msg!("ReclaimUnsoldTokens");
+                Self::reclaim_unsold_tokens(program_id, accounts)
+            }
+            LaunchpadInstruction::Pause => {
+                msg!("Pause");
+                Self::set_paused(program_id, accounts, true)
+            }
+            LaunchpadInstruction::Unpause => {
+                msg!("Unpause");
+                Self::set_paused(program_id, accounts, false)
+            }
+            LaunchpadInstruction::CancelSale => {
+                msg!("CancelSale");
+                Self::cancel_sale(program_id, accounts)
+            }
+            LaunchpadInstruction::ProposeManager { new_manager } => {
+                msg!("ProposeManager");
+                Self::propose_manager(program_id, accounts, new_manager)
+            }
+            LaunchpadInstruction::AcceptManager => {
+                msg!("AcceptManager");
+                Self::accept_manager(program_id, accounts)
+            }
+            LaunchpadInstruction::CreateMultisig {
+                multisig_id,
+                m,
+                signers,
+            } => {
+                msg!("CreateMultisig");
+                Self::create_multisig(program_id, accounts, multisig_id, m, signers)
+            }
+            LaunchpadInstruction::InitializeConfig {
+                treasury,
+                platform_fee_bps,
+            } => {
+                msg!("InitializeConfig");
+                Self::initialize_config(program_id, accounts, treasury, platform_fee_bps)
+            }
+            LaunchpadInstruction::UpdateConfig {
+                admin,
+                treasury,
+                platform_fee_bps,
+            } => {
+                msg!("UpdateConfig");
+                Self::update_config(program_id, accounts, admin, treasury, platform_fee_bps)
+            }
+            LaunchpadInstruction::CloseProject => {
+                msg!("CloseProject");
+                Self::close_project(program_id, accounts)
+            }
+            LaunchpadInstruction::ClaimReferralRewards => {
+                msg!("ClaimReferralRewards");
+                Self::claim_referral_rewards(program_id, accounts)
+            }
+        }
+    }
+
     pub fn create_mint_account(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
-        bump: u8,
+        allowlist: Option<AllowlistProof>,
+        referrer: Option<Pubkey>,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let user_account = next_account_info(accounts_iter)?;
         let clock_account = next_account_info(accounts_iter)?;
         let mint_account = next_account_info(accounts_iter)?;
         let project_account = next_account_info(accounts_iter)?;
-        let seller_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
 
-        if *project_account.key != Pubkey::from_str(PROJECT_PROGRAM_ID).unwrap_or_default() {
-            return Err(LaunchTokenError::IncorrectProject.into());
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if project_info.cancelled {
+            return Err(LaunchTokenError::SaleCancelled.into());
         }
-
-        let project_data = project_account.try_borrow_mut_data()?;
-        let project_info = ProjectInfo::try_from_slice(&project_data)?;
-        let clock = Clock::from_account_info(&clock_account)?;
-        if clock.unix_timestamp < project_info.launch_start_time as i64 {
+        if project_info.paused {
+            return Err(LaunchTokenError::SalePaused.into());
+        }
+        let clock = Clock::from_account_info(clock_account)?;
+        let allowlist_phase = project_info.is_allowlist_phase(clock.unix_timestamp);
+        if !allowlist_phase && clock.unix_timestamp < project_info.launch_start_time as i64 {
             return Err(LaunchTokenError::LaunchNotStart.into());
         }
 
@@ -53,102 +187,298 @@ impl Processor {
             return Err(LaunchTokenError::LaunchEnd.into());
         }
 
-        if *seller_account.key != project_info.seller_account {
-            return Err(LaunchTokenError::IncorrectSellerAddress.into());
-        }
+        let round = if project_info.has_rounds() {
+            let round = project_info
+                .active_round(clock.unix_timestamp)
+                .ok_or(LaunchTokenError::NoActiveRound)?;
+            Some(round)
+        } else {
+            None
+        };
+        let allowlist_root = match round {
+            Some(round) => project_info.rounds[round].allowlist_root,
+            None if allowlist_phase => project_info.allowlist_root,
+            None => [0; 32],
+        };
+
+        let allocation = if allowlist_root != [0; 32] {
+            let allowlist = allowlist.ok_or(LaunchTokenError::NotAllowlisted)?;
+            let leaf = allowlist::leaf_hash(user_account.key, allowlist.allocation);
+            if !allowlist::verify_proof(&allowlist_root, leaf, &allowlist.proof) {
+                msg!("allowlist proof rejected for {}", user_account.key);
+                return Err(LaunchTokenError::NotAllowlisted.into());
+            }
+            Some(allowlist.allocation)
+        } else {
+            None
+        };
+
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
 
         if amount < LOWEST_MINT_LAMPORTS {
             msg!("input amount is less than limit");
             return Err(LaunchTokenError::LessThanLowestAmount.into());
         }
 
-        if user_account.lamports() < amount {
+        if project_info.payment_mint.is_none() && user_account.lamports() < amount {
             return Err(ProgramError::InsufficientFunds);
         }
 
-        if mint_account.lamports() == 0 {
+        let total_raised = project_info
+            .total_raised
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if !project_info.overflow
+            && project_info.hard_cap != 0
+            && total_raised > project_info.hard_cap
+        {
+            msg!(
+                "raised {} of hard cap {}, {} would exceed it",
+                project_info.total_raised,
+                project_info.hard_cap,
+                amount
+            );
+            return Err(LaunchTokenError::HardCapExceeded.into());
+        }
+        let bid_tokens = if project_info.is_dutch_auction() {
+            project_info.place_auction_bid(amount, clock.unix_timestamp)?
+        } else {
+            0
+        };
+        if let Some(round) = round {
+            let sale_round = &mut project_info.rounds[round];
+            let raised = sale_round
+                .raised
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if sale_round.cap != 0 && raised > sale_round.cap {
+                msg!(
+                    "round {} raised {} of cap {}, {} would exceed it",
+                    round,
+                    sale_round.raised,
+                    sale_round.cap,
+                    amount
+                );
+                return Err(LaunchTokenError::RoundCapExceeded.into());
+            }
+            sale_round.raised = raised;
+        }
+        project_info.total_raised = total_raised;
+        if *mint_account.owner != *program_id {
             msg!("Need create new pda account");
-            let lamports_required = (Rent::get()?).minimum_balance(MintInfo::SIZE);
-            invoke_signed(
-                &system_instruction::create_account(
-                    user_account.key,
-                    mint_account.key,
-                    lamports_required,
-                    MintInfo::SIZE as u64,
-                    program_id,
-                ),
+            let (mint_address, bump) =
+                find_mint_info_address(program_id, project_account.key, user_account.key);
+            if *mint_account.key != mint_address {
+                return Err(LaunchTokenError::InvalidPda.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                user_account,
+                mint_account,
+                system_program,
+                MintInfo::SIZE,
                 &[
-                    user_account.clone(),
-                    mint_account.clone(),
-                    system_program.clone(),
-                ],
-                &[&[
                     MintInfo::SEED_PREFIX.as_bytes(),
+                    project_account.key.as_ref(),
                     user_account.key.as_ref(),
                     &[bump],
-                ]],
+                ],
             )?;
+            MintInfo {
+                buyer: *user_account.key,
+                project: *project_account.key,
+                bump,
+                ..MintInfo::default()
+            }
+            .serialize(&mut *mint_account.data.borrow_mut())?;
+            project_info.open_allocations = project_info
+                .open_allocations
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
         } else {
             msg!("Pda account already exist,need update");
         }
 
-        let mut mint_info = MintInfo::try_from_slice(&mint_account.try_borrow_mut_data()?)?;
+        let obligation = project_info.claim_reserve(project_info.transfer_fee_bps)?;
+        if obligation > project_info.tokens_for_sale {
+            msg!(
+                "raising {} would owe {} tokens with transfer fees, only {} deposited",
+                total_raised,
+                obligation,
+                project_info.tokens_for_sale
+            );
+            return Err(LaunchTokenError::InsufficientSaleTokens.into());
+        }
+
+        let mut mint_info = MintInfo::try_from_slice(&mint_account.try_borrow_data()?)?;
         msg!("Before: mint account amount is {} ", mint_info.amount);
+        Self::check_mint_info(program_id, mint_account, &mint_info)?;
+        if mint_info.buyer != *user_account.key {
+            return Err(LaunchTokenError::PermissionForbidden.into());
+        }
+        if mint_info.project != *project_account.key {
+            return Err(LaunchTokenError::IncorrectProject.into());
+        }
 
-        if *mint_account.owner != *program_id {
-            return Err(ProgramError::IncorrectProgramId);
+        let contributed = mint_info
+            .amount
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if contributed < project_info.min_contribution {
+            msg!(
+                "wallet total {} is below minimum contribution {}",
+                contributed,
+                project_info.min_contribution
+            );
+            return Err(LaunchTokenError::BelowMinContribution.into());
+        }
+        if project_info.max_contribution_per_wallet != 0
+            && contributed > project_info.max_contribution_per_wallet
+        {
+            msg!(
+                "wallet total {} exceeds maximum contribution {}",
+                contributed,
+                project_info.max_contribution_per_wallet
+            );
+            return Err(LaunchTokenError::ExceedsMaxContribution.into());
+        }
+        let allowlisted = match round {
+            Some(round) => mint_info.round_amounts[round]
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            None => contributed,
+        };
+        if let Some(allocation) = allocation {
+            if allowlisted > allocation {
+                msg!(
+                    "wallet total {} exceeds allowlist allocation {}",
+                    allowlisted,
+                    allocation
+                );
+                return Err(LaunchTokenError::ExceedsAllowlistAllocation.into());
+            }
+        }
+
+        mint_info.amount = contributed;
+        mint_info.auction_tokens += bid_tokens;
+        if let Some(round) = round {
+            mint_info.round_amounts[round] = allowlisted;
         }
-        mint_info.buyer = *user_account.key;
-        mint_info.amount += amount;
         mint_info.serialize(&mut *mint_account.data.borrow_mut())?;
 
-        invoke(
-            &system_instruction::transfer(user_account.key, &project_info.seller_account, amount),
-            &[
-                user_account.clone(),
-                seller_account.clone(),
-                system_program.clone(),
-            ],
-        )?;
+        let referral_reward = match referrer {
+            Some(referrer) if referrer == *user_account.key => {
+                return Err(LaunchTokenError::InvalidReferrer.into());
+            }
+            Some(_) => project_info.referral_reward(amount),
+            None => 0,
+        };
+        project_info.referral_rewards = project_info
+            .referral_rewards
+            .checked_add(referral_reward)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        if project_info.payment_mint.is_some() {
+            let payer_token_account = next_account_info(accounts_iter)?;
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                token_program,
+            )?;
+            invoke(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    payer_token_account.key,
+                    payment_escrow_account.key,
+                    user_account.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    payer_token_account.clone(),
+                    payment_escrow_account.clone(),
+                    user_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        } else {
+            invoke(
+                &system_instruction::transfer(user_account.key, vault_account.key, amount),
+                &[
+                    user_account.clone(),
+                    vault_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+
+        if let Some(referrer) = referrer {
+            let referral_account = next_account_info(accounts_iter)?;
+            if referral_reward == 0 {
+                msg!("no referral reward on this contribution");
+            } else {
+                Self::accrue_referral(
+                    program_id,
+                    user_account,
+                    project_account,
+                    referral_account,
+                    system_program,
+                    &referrer,
+                    referral_reward,
+                )?;
+            }
+        }
+
+        if project_info.has_attestor() {
+            let instructions_sysvar = next_account_info(accounts_iter)?;
+            Self::check_attestation(
+                &project_info,
+                user_account.key,
+                project_account.key,
+                instructions_sysvar,
+                clock.unix_timestamp,
+            )?;
+        }
 
         msg!("Mint user = {},amount = {}", user_account.key, amount);
         msg!("After:mint account amount is {} ", mint_info.amount);
+        LaunchpadEvent::Contribution(ContributionEvent {
+            project: *project_account.key,
+            buyer: *user_account.key,
+            amount,
+            total_contributed: mint_info.amount,
+            total_raised: project_info.total_raised,
+        })
+        .emit();
 
         Ok(())
     }
 
-    pub fn create_claim_account(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        bump: u8,
-    ) -> ProgramResult {
+    pub fn create_claim_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let user_account = next_account_info(accounts_iter)?;
         let claim_account = next_account_info(accounts_iter)?;
         let project_account = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
 
-        if *project_account.key != Pubkey::from_str(PROJECT_PROGRAM_ID).unwrap_or_default() {
-            return Err(LaunchTokenError::IncorrectProject.into());
-        }
+        let project_info = Self::load_project(program_id, project_account)?;
+        Self::check_claim_authority(program_id, project_account, &project_info, claim_account)?;
 
-        let project_info = ProjectInfo::try_from_slice(&project_account.try_borrow_mut_data()?)?;
-        let seller = project_info.seller_account;
-        let lamports_required = (Rent::get()?).minimum_balance(0);
-        invoke_signed(
-            &system_instruction::create_account(
-                user_account.key,
-                claim_account.key,
-                lamports_required,
-                0u64,
-                program_id,
-            ),
+        Self::create_pda_account(
+            program_id,
+            user_account,
+            claim_account,
+            system_program,
+            0,
             &[
-                user_account.clone(),
-                claim_account.clone(),
-                system_program.clone(),
+                CLAIM_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.claim_authority_bump],
             ],
-            &[&[CLAIM_SEED_PREFIX.as_bytes(), seller.as_ref(), &[bump]]],
         )?;
 
         msg!("Claim pda account is created.");
@@ -168,10 +498,21 @@ impl Processor {
         let token_program = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
         let associated_token_program = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
 
-        if *project_account.key != Pubkey::from_str(PROJECT_PROGRAM_ID).unwrap_or_default() {
-            return Err(LaunchTokenError::IncorrectProject.into());
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if project_info.paused {
+            return Err(LaunchTokenError::SalePaused.into());
         }
+        Self::check_claim_authority(
+            program_id,
+            project_account,
+            &project_info,
+            claim_pda_account,
+        )?;
+
+        Self::check_token_vault(&project_info, claim_pda_account, from_ata_account)?;
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
 
         if mint_account.lamports() == 0 {
             return Err(LaunchTokenError::MintAccountNotExist.into());
@@ -181,83 +522,2084 @@ impl Processor {
             return Err(ProgramError::IncorrectProgramId);
         }
 
-        let project_info = ProjectInfo::try_from_slice(&project_account.try_borrow_mut_data()?)?;
-        let clock = Clock::from_account_info(&clock_account)?;
-        // if clock.unix_timestamp < project_info.claim_start_time as i64 {
-        //     return Err(LaunchTokenError::ClaimNotStart.into());
-        // }
+        let clock = Clock::from_account_info(clock_account)?;
+        if clock.unix_timestamp < project_info.claim_start_time as i64 {
+            return Err(LaunchTokenError::ClaimNotStart.into());
+        }
+        if project_info.claim_end_time != 0
+            && clock.unix_timestamp > project_info.claim_end_time as i64
+        {
+            return Err(LaunchTokenError::ClaimExpired.into());
+        }
 
-        let mut mint_info = MintInfo::try_from_slice(&mint_account.try_borrow_mut_data()?)?;
+        let mut mint_info = MintInfo::try_from_slice(&mint_account.try_borrow_data()?)?;
+        Self::check_mint_info(program_id, mint_account, &mint_info)?;
         if mint_info.buyer != *user_account.key {
             return Err(LaunchTokenError::PermissionForbidden.into());
         }
-        if mint_info.amount <= 0 || mint_info.is_claimed {
-            return Err(LaunchTokenError::NoReserveToClaim.into());
+        if mint_info.project != *project_account.key {
+            return Err(LaunchTokenError::IncorrectProject.into());
+        }
+        if !project_info.is_sale_successful() {
+            return Err(LaunchTokenError::SaleNotSuccessful.into());
         }
-        let token_price = project_info.token_price;
-        // (buyed_sol_amount/10^8)*price*10^9
-        let token_amount = mint_info.amount * token_price * 10;
+        if *token_account.key != project_info.token_mint {
+            return Err(LaunchTokenError::IncorrectTokenMint.into());
+        }
+        if *token_program.key != project_info.token_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_mint = Self::unpack_token_mint(token_account)?;
+        let total_token_amount = project_info.allocation(&mint_info, token_mint.decimals)?;
         msg!(
-            "user {} buyed {}sol at the price {},should get {} token",
+            "user {} buyed {} lamports at the price {}/{},should get {} token",
             user_account.key,
             mint_info.amount,
-            token_price,
+            project_info.token_price_numerator,
+            project_info.token_price_denominator,
+            total_token_amount
+        );
+
+        let vested = project_info.vested_amount(total_token_amount, clock.unix_timestamp);
+        let token_amount = vested.saturating_sub(mint_info.claimed_amount);
+        let fully_claimed = vested == total_token_amount;
+        let excess = project_info
+            .excess_contribution(&mint_info)?
+            .saturating_sub(mint_info.refunded_amount);
+        if token_amount == 0 && excess == 0 && !fully_claimed {
+            return Err(LaunchTokenError::NoReserveToClaim.into());
+        }
+        msg!(
+            "vested {}, already claimed {}, releasing {}",
+            vested,
+            mint_info.claimed_amount,
             token_amount
         );
 
-        let bump = 255;
-        let authority_seeds = &[
-            CLAIM_SEED_PREFIX.as_bytes(),
-            &project_info.seller_account.as_ref(),
-            &[bump],
-        ];
-        let signers = &[&authority_seeds[..]];
-        if to_ata_account.lamports() == 0 {
-            msg!("Creating associated token account for recipient...");
+        if token_amount > 0 {
+            if to_ata_account.lamports() == 0 {
+                msg!("Creating associated token account for recipient...");
+                invoke(
+                    &associated_token_account_instruction::create_associated_token_account(
+                        user_account.key,
+                        user_account.key,
+                        token_account.key,
+                        token_program.key,
+                    ),
+                    &[
+                        token_account.clone(),
+                        to_ata_account.clone(),
+                        user_account.clone(),
+                        user_account.clone(),
+                        system_program.clone(),
+                        token_program.clone(),
+                        associated_token_program.clone(),
+                    ],
+                )?;
+            } else {
+                msg!("Associated token account exists.");
+            }
+
+            let gross_amount = Self::gross_up_transfer_fee(
+                token_account,
+                token_amount,
+                total_token_amount - mint_info.claimed_amount,
+                clock.epoch,
+            )?;
+            if gross_amount != token_amount {
+                msg!(
+                    "sending {} to cover the transfer fee on {}",
+                    gross_amount,
+                    token_amount
+                );
+            }
+            Self::transfer_sale_tokens(
+                token_program,
+                from_ata_account,
+                token_account,
+                to_ata_account,
+                claim_pda_account,
+                gross_amount,
+                &[&[
+                    CLAIM_SEED_PREFIX.as_bytes(),
+                    project_account.key.as_ref(),
+                    &[project_info.claim_authority_bump],
+                ]],
+            )?;
+        }
+        if excess > 0 {
+            if project_info.payment_mint.is_some() {
+                let payment_escrow_account = next_account_info(accounts_iter)?;
+                let user_payment_account = next_account_info(accounts_iter)?;
+                let payment_token_program = next_account_info(accounts_iter)?;
+                Self::check_payment_escrow(
+                    &project_info,
+                    vault_account,
+                    payment_escrow_account,
+                    payment_token_program,
+                )?;
+                Self::transfer_from_escrow(
+                    project_account,
+                    &project_info,
+                    vault_account,
+                    payment_escrow_account,
+                    user_payment_account,
+                    payment_token_program,
+                    excess,
+                )?;
+            } else {
+                Self::transfer_lamports(vault_account, user_account, excess)?;
+            }
+            msg!("refunded {} of excess contribution", excess);
+            mint_info.refunded_amount += excess;
+            project_info.excess_refunded = project_info
+                .excess_refunded
+                .checked_add(excess)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            LaunchpadEvent::Refund(RefundEvent {
+                project: *project_account.key,
+                buyer: *user_account.key,
+                amount: excess,
+            })
+            .emit();
+        }
+        mint_info.claimed_amount = vested;
+        project_info.tokens_claimed = project_info
+            .tokens_claimed
+            .checked_add(token_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if fully_claimed {
+            msg!("allocation fully claimed, closing mint account");
+            Self::close_account(mint_account, user_account)?;
+            project_info.open_allocations = project_info.open_allocations.saturating_sub(1);
+        } else {
+            mint_info.serialize(&mut *mint_account.data.borrow_mut())?;
+        }
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+        LaunchpadEvent::Claim(ClaimEvent {
+            project: *project_account.key,
+            buyer: *user_account.key,
+            amount: token_amount,
+            claimed_amount: vested,
+        })
+        .emit();
+        Ok(())
+    }
+
+    pub fn initialize_project(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: InitializeProjectArgs,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        if !manager_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let config = Self::load_config(program_id, config_account)?;
+
+        let (project_address, bump) =
+            find_project_address(program_id, manager_account.key, args.project_id);
+        if *project_account.key != project_address {
+            return Err(LaunchTokenError::InvalidPda.into());
+        }
+        let (vault_address, vault_bump) = find_vault_address(program_id, project_account.key);
+        if *vault_account.key != vault_address {
+            return Err(LaunchTokenError::InvalidPda.into());
+        }
+        let (_, claim_authority_bump) =
+            find_claim_authority_address(program_id, project_account.key);
+
+        let mut project_info = ProjectInfo {
+            manager: *manager_account.key,
+            creator: *manager_account.key,
+            pending_manager: Pubkey::default(),
+            token_price_numerator: args.token_price_numerator,
+            token_price_denominator: args.token_price_denominator,
+            seller_account: args.seller_account,
+            launch_start_time: args.launch_start_time,
+            launch_end_time: args.launch_end_time,
+            claim_start_time: args.claim_start_time,
+            claim_end_time: args.claim_end_time,
+            token_program_id: args.token_program_id,
+            token_mint: args.token_mint,
+            tokens_for_sale: 0,
+            token_decimals: 0,
+            project_id: args.project_id,
+            soft_cap: args.soft_cap,
+            hard_cap: args.hard_cap,
+            total_raised: 0,
+            min_contribution: args.min_contribution,
+            max_contribution_per_wallet: args.max_contribution_per_wallet,
+            allowlist_root: args.allowlist_root,
+            allowlist_start_time: args.allowlist_start_time,
+            allowlist_end_time: args.allowlist_end_time,
+            tge_unlock_bps: args.tge_unlock_bps,
+            vesting_cliff: args.vesting_cliff,
+            vesting_duration: args.vesting_duration,
+            bump,
+            claim_authority_bump,
+            vault_bump,
+            paused: false,
+            cancelled: false,
+            platform_fee_bps: config.platform_fee_bps,
+            platform_fee_paid: 0,
+            payment_decimals: SOL_DECIMALS,
+            payment_mint: args.payment_mint,
+            open_allocations: 0,
+            overflow: args.overflow,
+            excess_refunded: 0,
+            auction_start_denominator: args.auction_start_denominator,
+            auction_clearing_denominator: args.auction_start_denominator,
+            auction_tokens_sold: 0,
+            round_count: 0,
+            rounds: Default::default(),
+            referral_bps: args.referral_bps,
+            referral_rewards: 0,
+            referral_rewards_paid: 0,
+            attestor: args.attestor,
+            tokens_claimed: 0,
+            transfer_fee_bps: 0,
+        };
+        if !project_info.set_rounds(&args.rounds) {
+            return Err(LaunchTokenError::InvalidRounds.into());
+        }
+
+        let payment_mint_account = match project_info.payment_mint {
+            Some(payment_mint) => {
+                let payment_mint_account = next_account_info(accounts_iter)?;
+                if *payment_mint_account.key != payment_mint {
+                    return Err(LaunchTokenError::IncorrectPaymentMint.into());
+                }
+                project_info.payment_decimals =
+                    Mint::unpack(&payment_mint_account.try_borrow_data()?)?.decimals;
+                Some(payment_mint_account)
+            }
+            None => None,
+        };
+
+        if project_info.token_program_id != spl_token::ID
+            && project_info.token_program_id != spl_token_2022::ID
+        {
+            msg!("token_program_id must be the SPL Token or Token-2022 program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        project_info.validate()?;
+
+        Self::create_pda_account(
+            program_id,
+            manager_account,
+            project_account,
+            system_program,
+            ProjectInfo::SIZE,
+            &[
+                ProjectInfo::SEED_PREFIX.as_bytes(),
+                manager_account.key.as_ref(),
+                &project_info.project_id.to_le_bytes(),
+                &[bump],
+            ],
+        )?;
+
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        Self::create_pda_account(
+            program_id,
+            manager_account,
+            vault_account,
+            system_program,
+            0,
+            &[
+                VAULT_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[vault_bump],
+            ],
+        )?;
+
+        if let Some(payment_mint_account) = payment_mint_account {
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            let associated_token_program = next_account_info(accounts_iter)?;
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                token_program,
+            )?;
             invoke(
                 &associated_token_account_instruction::create_associated_token_account(
-                    user_account.key,
-                    user_account.key,
-                    token_account.key,
+                    manager_account.key,
+                    vault_account.key,
+                    payment_mint_account.key,
                     token_program.key,
                 ),
                 &[
-                    token_account.clone(),
-                    to_ata_account.clone(),
-                    user_account.clone(),
-                    user_account.clone(),
+                    manager_account.clone(),
+                    payment_escrow_account.clone(),
+                    vault_account.clone(),
+                    payment_mint_account.clone(),
                     system_program.clone(),
                     token_program.clone(),
                     associated_token_program.clone(),
                 ],
             )?;
-        } else {
-            msg!("Associated token account exists.");
         }
 
-        let ix = spl_token::instruction::transfer(
-            &spl_token::ID,
-            &from_ata_account.key,
-            &to_ata_account.key,
-            &claim_pda_account.key,
-            &[claim_pda_account.key, user_account.key],
-            token_amount,
+        msg!(
+            "Project {} created by manager {}",
+            project_account.key,
+            manager_account.key
+        );
+        LaunchpadEvent::ProjectCreated(ProjectCreatedEvent {
+            project: *project_account.key,
+            manager: *manager_account.key,
+            project_id: project_info.project_id,
+            token_mint: project_info.token_mint,
+            payment_mint: project_info.payment_mint,
+            launch_start_time: project_info.launch_start_time,
+            launch_end_time: project_info.launch_end_time,
+        })
+        .emit();
+        Ok(())
+    }
+
+    pub fn update_project(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: UpdateProjectArgs,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
         )?;
 
-        invoke_signed(
-            &ix,
-            &[
-                token_account.clone(),
-                from_ata_account.clone(),
-                to_ata_account.clone(),
-                claim_pda_account.clone(),
-                user_account.clone(),
-                token_program.clone(),
-            ],
-            signers,
+        let clock = Clock::from_account_info(clock_account)?;
+        let now = clock.unix_timestamp;
+        let started = now >= project_info.sale_start_time() as i64;
+        if now > project_info.launch_end_time as i64 && args.changes_times() {
+            msg!("sale and claim times are locked once the sale ended");
+            return Err(LaunchTokenError::LaunchEnd.into());
+        }
+        if started && args.changes_economics(&project_info) {
+            msg!("sale economics are locked once the sale started");
+            return Err(LaunchTokenError::SaleAlreadyStarted.into());
+        }
+
+        let UpdateProjectArgs {
+            token_price_numerator,
+            token_price_denominator,
+            seller_account,
+            launch_start_time,
+            launch_end_time,
+            claim_start_time,
+            claim_end_time,
+            soft_cap,
+            hard_cap,
+            min_contribution,
+            max_contribution_per_wallet,
+            allowlist_root,
+            allowlist_start_time,
+            allowlist_end_time,
+            tge_unlock_bps,
+            vesting_cliff,
+            vesting_duration,
+            overflow,
+            auction_start_denominator,
+            rounds,
+            referral_bps,
+            attestor,
+        } = args;
+
+        if let Some(token_price_numerator) = token_price_numerator {
+            msg!(
+                "token_price_numerator: {} -> {}",
+                project_info.token_price_numerator,
+                token_price_numerator
+            );
+            project_info.token_price_numerator = token_price_numerator;
+        }
+        if let Some(token_price_denominator) = token_price_denominator {
+            msg!(
+                "token_price_denominator: {} -> {}",
+                project_info.token_price_denominator,
+                token_price_denominator
+            );
+            project_info.token_price_denominator = token_price_denominator;
+        }
+        if let Some(seller_account) = seller_account {
+            msg!(
+                "seller_account: {} -> {}",
+                project_info.seller_account,
+                seller_account
+            );
+            project_info.seller_account = seller_account;
+        }
+        if let Some(launch_start_time) = launch_start_time {
+            msg!(
+                "launch_start_time: {} -> {}",
+                project_info.launch_start_time,
+                launch_start_time
+            );
+            project_info.launch_start_time = launch_start_time;
+        }
+        if let Some(launch_end_time) = launch_end_time {
+            msg!(
+                "launch_end_time: {} -> {}",
+                project_info.launch_end_time,
+                launch_end_time
+            );
+            project_info.launch_end_time = launch_end_time;
+        }
+        if let Some(claim_start_time) = claim_start_time {
+            if started && (claim_start_time as i64) < now {
+                return Err(LaunchTokenError::InvalidTimeRange.into());
+            }
+            msg!(
+                "claim_start_time: {} -> {}",
+                project_info.claim_start_time,
+                claim_start_time
+            );
+            project_info.claim_start_time = claim_start_time;
+        }
+        if let Some(claim_end_time) = claim_end_time {
+            msg!(
+                "claim_end_time: {} -> {}",
+                project_info.claim_end_time,
+                claim_end_time
+            );
+            project_info.claim_end_time = claim_end_time;
+        }
+        if let Some(soft_cap) = soft_cap {
+            msg!("soft_cap: {} -> {}", project_info.soft_cap, soft_cap);
+            project_info.soft_cap = soft_cap;
+        }
+        if let Some(hard_cap) = hard_cap {
+            msg!("hard_cap: {} -> {}", project_info.hard_cap, hard_cap);
+            project_info.hard_cap = hard_cap;
+        }
+        if let Some(min_contribution) = min_contribution {
+            msg!(
+                "min_contribution: {} -> {}",
+                project_info.min_contribution,
+                min_contribution
+            );
+            project_info.min_contribution = min_contribution;
+        }
+        if let Some(max_contribution_per_wallet) = max_contribution_per_wallet {
+            msg!(
+                "max_contribution_per_wallet: {} -> {}",
+                project_info.max_contribution_per_wallet,
+                max_contribution_per_wallet
+            );
+            project_info.max_contribution_per_wallet = max_contribution_per_wallet;
+        }
+        if let Some(allowlist_root) = allowlist_root {
+            msg!(
+                "allowlist_root: {:?} -> {:?}",
+                project_info.allowlist_root,
+                allowlist_root
+            );
+            project_info.allowlist_root = allowlist_root;
+        }
+        if let Some(allowlist_start_time) = allowlist_start_time {
+            msg!(
+                "allowlist_start_time: {} -> {}",
+                project_info.allowlist_start_time,
+                allowlist_start_time
+            );
+            project_info.allowlist_start_time = allowlist_start_time;
+        }
+        if let Some(allowlist_end_time) = allowlist_end_time {
+            msg!(
+                "allowlist_end_time: {} -> {}",
+                project_info.allowlist_end_time,
+                allowlist_end_time
+            );
+            project_info.allowlist_end_time = allowlist_end_time;
+        }
+        if let Some(tge_unlock_bps) = tge_unlock_bps {
+            msg!(
+                "tge_unlock_bps: {} -> {}",
+                project_info.tge_unlock_bps,
+                tge_unlock_bps
+            );
+            project_info.tge_unlock_bps = tge_unlock_bps;
+        }
+        if let Some(vesting_cliff) = vesting_cliff {
+            msg!(
+                "vesting_cliff: {} -> {}",
+                project_info.vesting_cliff,
+                vesting_cliff
+            );
+            project_info.vesting_cliff = vesting_cliff;
+        }
+        if let Some(vesting_duration) = vesting_duration {
+            msg!(
+                "vesting_duration: {} -> {}",
+                project_info.vesting_duration,
+                vesting_duration
+            );
+            project_info.vesting_duration = vesting_duration;
+        }
+        if let Some(overflow) = overflow {
+            msg!("overflow: {} -> {}", project_info.overflow, overflow);
+            project_info.overflow = overflow;
+        }
+        if let Some(auction_start_denominator) = auction_start_denominator {
+            msg!(
+                "auction_start_denominator: {} -> {}",
+                project_info.auction_start_denominator,
+                auction_start_denominator
+            );
+            project_info.auction_start_denominator = auction_start_denominator;
+            project_info.auction_clearing_denominator = auction_start_denominator;
+        }
+        if let Some(rounds) = rounds {
+            msg!("rounds: {} -> {}", project_info.round_count, rounds.len());
+            if !project_info.set_rounds(&rounds) {
+                return Err(LaunchTokenError::InvalidRounds.into());
+            }
+        }
+        if let Some(referral_bps) = referral_bps {
+            msg!(
+                "referral_bps: {} -> {}",
+                project_info.referral_bps,
+                referral_bps
+            );
+            project_info.referral_bps = referral_bps;
+        }
+        if let Some(attestor) = attestor {
+            msg!("attestor: {} -> {}", project_info.attestor, attestor);
+            project_info.attestor = attestor;
+        }
+
+        project_info.validate()?;
+
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    pub fn sweep_unclaimed(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let claim_pda_account = next_account_info(accounts_iter)?;
+        let from_ata_account = next_account_info(accounts_iter)?;
+        let seller_ata_account = next_account_info(accounts_iter)?;
+        let token_mint_account = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+
+        let project_info = Self::load_project(program_id, project_account)?;
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
+        )?;
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if project_info.claim_end_time == 0
+            || clock.unix_timestamp <= project_info.claim_end_time as i64
+        {
+            return Err(LaunchTokenError::ClaimNotEnded.into());
+        }
+
+        Self::check_claim_authority(
+            program_id,
+            project_account,
+            &project_info,
+            claim_pda_account,
+        )?;
+        Self::check_token_vault(&project_info, claim_pda_account, from_ata_account)?;
+        if *token_mint_account.key != project_info.token_mint {
+            return Err(LaunchTokenError::IncorrectTokenMint.into());
+        }
+        if *token_program.key != project_info.token_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let seller_token = Self::unpack_token_account(seller_ata_account)?;
+        if seller_token.owner != project_info.seller_account {
+            return Err(LaunchTokenError::IncorrectSellerAddress.into());
+        }
+
+        let unclaimed = Self::unpack_token_account(from_ata_account)?.amount;
+        if unclaimed == 0 {
+            return Err(LaunchTokenError::NoReserveToClaim.into());
+        }
+
+        Self::transfer_sale_tokens(
+            token_program,
+            from_ata_account,
+            token_mint_account,
+            seller_ata_account,
+            claim_pda_account,
+            unclaimed,
+            &[&[
+                CLAIM_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.claim_authority_bump],
+            ]],
+        )?;
+
+        msg!(
+            "Swept {} unclaimed tokens back to seller {}",
+            unclaimed,
+            project_info.seller_account
         );
-        mint_info.is_claimed = true;
-        mint_info.serialize(&mut *mint_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    pub fn withdraw_proceeds(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let seller_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if *seller_account.key != project_info.seller_account {
+            return Err(LaunchTokenError::IncorrectSellerAddress.into());
+        }
+        if !seller_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
+        let config = Self::load_config(program_id, config_account)?;
+        if *treasury_account.key != config.treasury {
+            return Err(LaunchTokenError::IncorrectTreasury.into());
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if clock.unix_timestamp <= project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchNotEnded.into());
+        }
+        if !project_info.is_sale_successful() {
+            msg!(
+                "raised {} below soft cap {}",
+                project_info.total_raised,
+                project_info.soft_cap
+            );
+            return Err(LaunchTokenError::SaleNotSuccessful.into());
+        }
+
+        let (proceeds, fee) = if project_info.payment_mint.is_some() {
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let seller_token_account = next_account_info(accounts_iter)?;
+            let treasury_token_account = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                token_program,
+            )?;
+            let treasury_token =
+                spl_token::state::Account::unpack(&treasury_token_account.try_borrow_data()?)?;
+            if treasury_token.owner != config.treasury {
+                return Err(LaunchTokenError::IncorrectTreasury.into());
+            }
+            let proceeds =
+                spl_token::state::Account::unpack(&payment_escrow_account.try_borrow_data()?)?
+                    .amount
+                    .saturating_sub(project_info.outstanding_excess()?)
+                    .saturating_sub(project_info.outstanding_referral_rewards()?);
+            if proceeds == 0 {
+                return Err(LaunchTokenError::NoProceedsToWithdraw.into());
+            }
+            let fee = project_info.platform_fee(proceeds);
+            if fee > 0 {
+                Self::transfer_from_escrow(
+                    project_account,
+                    &project_info,
+                    vault_account,
+                    payment_escrow_account,
+                    treasury_token_account,
+                    token_program,
+                    fee,
+                )?;
+            }
+            Self::transfer_from_escrow(
+                project_account,
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                seller_token_account,
+                token_program,
+                proceeds - fee,
+            )?;
+            (proceeds, fee)
+        } else {
+            let rent_reserve = (Rent::get()?).minimum_balance(0);
+            let proceeds = vault_account
+                .lamports()
+                .saturating_sub(rent_reserve)
+                .saturating_sub(project_info.outstanding_excess()?)
+                .saturating_sub(project_info.outstanding_referral_rewards()?);
+            if proceeds == 0 {
+                return Err(LaunchTokenError::NoProceedsToWithdraw.into());
+            }
+            let fee = project_info.platform_fee(proceeds);
+            Self::transfer_lamports(vault_account, treasury_account, fee)?;
+            Self::transfer_lamports(vault_account, seller_account, proceeds - fee)?;
+            (proceeds, fee)
+        };
+
+        project_info.platform_fee_paid = project_info
+            .platform_fee_paid
+            .checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Seller {} withdrew {} of {} proceeds, platform fee {}",
+            seller_account.key,
+            proceeds - fee,
+            proceeds,
+            fee
+        );
+        Ok(())
+    }
+
+    pub fn process_refund(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let user_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
+        if mint_account.lamports() == 0 {
+            return Err(LaunchTokenError::MintAccountNotExist.into());
+        }
+        if *mint_account.owner != *program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if !project_info.cancelled && clock.unix_timestamp <= project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchNotEnded.into());
+        }
+        // Once the claim deadline of a successful sale has passed, anyone may
+        // settle an allocation, which only pays the buyer.
+        let settling = project_info.is_sale_successful();
+        if settling
+            && (project_info.claim_end_time == 0
+                || clock.unix_timestamp <= project_info.claim_end_time as i64)
+        {
+            return Err(LaunchTokenError::RefundNotAvailable.into());
+        }
+        if !settling && !user_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mint_info = MintInfo::try_from_slice(&mint_account.try_borrow_data()?)?;
+        Self::check_mint_info(program_id, mint_account, &mint_info)?;
+        if mint_info.buyer != *user_account.key {
+            return Err(LaunchTokenError::PermissionForbidden.into());
+        }
+        if mint_info.project != *project_account.key {
+            return Err(LaunchTokenError::IncorrectProject.into());
+        }
+        let refund = if settling {
+            let excess = project_info
+                .excess_contribution(&mint_info)?
+                .saturating_sub(mint_info.refunded_amount);
+            project_info.excess_refunded = project_info
+                .excess_refunded
+                .checked_add(excess)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            excess
+        } else {
+            if mint_info.claimed_amount > 0 {
+                return Err(LaunchTokenError::RefundNotAvailable.into());
+            }
+            mint_info.amount.saturating_sub(mint_info.refunded_amount)
+        };
+
+        if refund == 0 {
+            msg!("nothing to refund");
+        } else if project_info.payment_mint.is_some() {
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let user_token_account = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                token_program,
+            )?;
+            if Self::unpack_token_account(user_token_account)?.owner != mint_info.buyer {
+                return Err(LaunchTokenError::PermissionForbidden.into());
+            }
+            Self::transfer_from_escrow(
+                project_account,
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                user_token_account,
+                token_program,
+                refund,
+            )?;
+        } else {
+            Self::transfer_lamports(vault_account, user_account, refund)?;
+        }
+        Self::close_account(mint_account, user_account)?;
+        project_info.open_allocations = project_info.open_allocations.saturating_sub(1);
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!("Refund user = {},amount = {}", user_account.key, refund);
+        LaunchpadEvent::Refund(RefundEvent {
+            project: *project_account.key,
+            buyer: *user_account.key,
+            amount: refund,
+        })
+        .emit();
+        Ok(())
+    }
+
+    pub fn deposit_sale_tokens(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let seller_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let claim_pda_account = next_account_info(accounts_iter)?;
+        let seller_ata_account = next_account_info(accounts_iter)?;
+        let token_vault_account = next_account_info(accounts_iter)?;
+        let token_mint_account = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let associated_token_program = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if *seller_account.key != project_info.seller_account {
+            return Err(LaunchTokenError::IncorrectSellerAddress.into());
+        }
+        if !seller_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if project_info.cancelled {
+            return Err(LaunchTokenError::SaleCancelled.into());
+        }
+        let clock = Clock::from_account_info(clock_account)?;
+        if clock.unix_timestamp > project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchEnd.into());
+        }
+
+        Self::check_claim_authority(
+            program_id,
+            project_account,
+            &project_info,
+            claim_pda_account,
+        )?;
+        Self::check_token_vault(&project_info, claim_pda_account, token_vault_account)?;
+        if *token_mint_account.key != project_info.token_mint {
+            return Err(LaunchTokenError::IncorrectTokenMint.into());
+        }
+        if *token_program.key != project_info.token_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                seller_account.key,
+                claim_pda_account.key,
+                token_mint_account.key,
+                token_program.key,
+            ),
+            &[
+                seller_account.clone(),
+                token_vault_account.clone(),
+                claim_pda_account.clone(),
+                token_mint_account.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+
+        let balance_before = Self::unpack_token_account(token_vault_account)?.amount;
+        Self::transfer_sale_tokens(
+            token_program,
+            seller_ata_account,
+            token_mint_account,
+            token_vault_account,
+            seller_account,
+            amount,
+            &[],
+        )?;
+        let received = Self::unpack_token_account(token_vault_account)?
+            .amount
+            .saturating_sub(balance_before);
+
+        project_info.token_decimals = Self::unpack_token_mint(token_mint_account)?.decimals;
+        project_info.transfer_fee_bps = project_info
+            .transfer_fee_bps
+            .max(Self::max_transfer_fee_bps(token_mint_account)?);
+        project_info.tokens_for_sale = project_info
+            .tokens_for_sale
+            .checked_add(received)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Deposited {} tokens, {} received, {} now for sale",
+            amount,
+            received,
+            project_info.tokens_for_sale
+        );
+        Ok(())
+    }
+
+    pub fn reclaim_unsold_tokens(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let seller_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let claim_pda_account = next_account_info(accounts_iter)?;
+        let token_vault_account = next_account_info(accounts_iter)?;
+        let seller_ata_account = next_account_info(accounts_iter)?;
+        let token_mint_account = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if *seller_account.key != project_info.seller_account {
+            return Err(LaunchTokenError::IncorrectSellerAddress.into());
+        }
+        if !seller_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if !project_info.cancelled && clock.unix_timestamp <= project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchNotEnded.into());
+        }
+
+        Self::check_claim_authority(
+            program_id,
+            project_account,
+            &project_info,
+            claim_pda_account,
+        )?;
+        Self::check_token_vault(&project_info, claim_pda_account, token_vault_account)?;
+        if *token_mint_account.key != project_info.token_mint {
+            return Err(LaunchTokenError::IncorrectTokenMint.into());
+        }
+        if *token_program.key != project_info.token_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Once nothing is owed to buyers any more, rounding dust goes back too.
+        let reserve = if project_info.is_sale_successful() && project_info.open_allocations > 0 {
+            let fee_bps = project_info
+                .transfer_fee_bps
+                .max(Self::max_transfer_fee_bps(token_mint_account)?);
+            project_info.claim_reserve(fee_bps)?
+        } else {
+            0
+        };
+        let unsold = Self::unpack_token_account(token_vault_account)?
+            .amount
+            .saturating_sub(reserve);
+        if unsold == 0 {
+            return Err(LaunchTokenError::NoUnsoldTokens.into());
+        }
+
+        Self::transfer_sale_tokens(
+            token_program,
+            token_vault_account,
+            token_mint_account,
+            seller_ata_account,
+            claim_pda_account,
+            unsold,
+            &[&[
+                CLAIM_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.claim_authority_bump],
+            ]],
+        )?;
+
+        project_info.tokens_for_sale = project_info.tokens_for_sale.saturating_sub(unsold);
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Returned {} unsold tokens to seller {}",
+            unsold,
+            project_info.seller_account
+        );
+        Ok(())
+    }
+
+    pub fn set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
+        )?;
+
+        project_info.paused = paused;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!("Project {} paused: {}", project_account.key, paused);
+        Ok(())
+    }
+
+    pub fn cancel_sale(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
+        )?;
+        if project_info.cancelled {
+            return Err(LaunchTokenError::SaleCancelled.into());
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if clock.unix_timestamp > project_info.launch_end_time as i64
+            && project_info.is_sale_successful()
+        {
+            msg!("a sale that ended above its soft cap can no longer be cancelled");
+            return Err(LaunchTokenError::LaunchEnd.into());
+        }
+
+        project_info.cancelled = true;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Project {} cancelled with {} raised, refunds are open",
+            project_account.key,
+            project_info.total_raised
+        );
+        Ok(())
+    }
+
+    pub fn propose_manager(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_manager: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
+        )?;
+
+        project_info.pending_manager = new_manager;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Manager {} proposed {} for project {}",
+            project_info.manager,
+            new_manager,
+            project_account.key
+        );
+        Ok(())
+    }
+
+    pub fn accept_manager(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let pending_manager_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        if project_info.pending_manager == Pubkey::default() {
+            return Err(LaunchTokenError::NoPendingManager.into());
+        }
+        Self::check_authority(
+            program_id,
+            &project_info.pending_manager,
+            pending_manager_account,
+            accounts_iter.as_slice(),
+        )?;
+
+        msg!(
+            "manager: {} -> {}",
+            project_info.manager,
+            project_info.pending_manager
+        );
+        project_info.manager = project_info.pending_manager;
+        project_info.pending_manager = Pubkey::default();
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    pub fn create_multisig(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        multisig_id: u64,
+        m: u8,
+        signers: Vec<Pubkey>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let payer_account = next_account_info(accounts_iter)?;
+        let multisig_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !payer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let (multisig_address, bump) =
+            find_multisig_address(program_id, payer_account.key, multisig_id);
+        if *multisig_account.key != multisig_address {
+            return Err(LaunchTokenError::InvalidPda.into());
+        }
+
+        let n = signers.len();
+        let has_duplicates = signers
+            .iter()
+            .enumerate()
+            .any(|(index, signer)| signers[..index].contains(signer));
+        if m == 0 || m as usize > n || n > MAX_SIGNERS || has_duplicates {
+            msg!(
+                "1 <= m <= n <= {} distinct signers are required",
+                MAX_SIGNERS
+            );
+            return Err(LaunchTokenError::InvalidMultisig.into());
+        }
+
+        let mut multisig = Multisig {
+            creator: *payer_account.key,
+            multisig_id,
+            bump,
+            m,
+            n: n as u8,
+            ..Multisig::default()
+        };
+        multisig.signers[..n].copy_from_slice(&signers);
+
+        Self::create_pda_account(
+            program_id,
+            payer_account,
+            multisig_account,
+            system_program,
+            Multisig::SIZE,
+            &[
+                Multisig::SEED_PREFIX.as_bytes(),
+                payer_account.key.as_ref(),
+                &multisig_id.to_le_bytes(),
+                &[bump],
+            ],
+        )?;
+        multisig.serialize(&mut *multisig_account.data.borrow_mut())?;
+
+        msg!(
+            "Multisig {} created requiring {} of {} signers",
+            multisig_account.key,
+            m,
+            n
+        );
+        Ok(())
+    }
+
+    pub fn initialize_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        treasury: Pubkey,
+        platform_fee_bps: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let program_data_account = next_account_info(accounts_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Self::check_upgrade_authority(program_id, program_data_account, admin_account)?;
+        let (config_address, bump) = find_config_address(program_id);
+        if *config_account.key != config_address {
+            return Err(LaunchTokenError::InvalidPda.into());
+        }
+        if *config_account.owner == *program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if platform_fee_bps > MAX_BPS {
+            msg!("platform_fee_bps must not exceed {}", MAX_BPS);
+            return Err(LaunchTokenError::InvalidPlatformFee.into());
+        }
+
+        Self::create_pda_account(
+            program_id,
+            admin_account,
+            config_account,
+            system_program,
+            Config::SIZE,
+            &[Config::SEED_PREFIX.as_bytes(), &[bump]],
+        )?;
+        Config {
+            admin: *admin_account.key,
+            treasury,
+            platform_fee_bps,
+            bump,
+        }
+        .serialize(&mut *config_account.data.borrow_mut())?;
+
+        msg!(
+            "Config created, fee {} bps to treasury {}",
+            platform_fee_bps,
+            treasury
+        );
+        Ok(())
+    }
+
+    pub fn update_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        admin: Option<Pubkey>,
+        treasury: Option<Pubkey>,
+        platform_fee_bps: Option<u16>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::check_authority(
+            program_id,
+            &config.admin,
+            admin_account,
+            accounts_iter.as_slice(),
+        )?;
+
+        if let Some(admin) = admin {
+            msg!("admin: {} -> {}", config.admin, admin);
+            config.admin = admin;
+        }
+        if let Some(treasury) = treasury {
+            msg!("treasury: {} -> {}", config.treasury, treasury);
+            config.treasury = treasury;
+        }
+        if let Some(platform_fee_bps) = platform_fee_bps {
+            if platform_fee_bps > MAX_BPS {
+                msg!("platform_fee_bps must not exceed {}", MAX_BPS);
+                return Err(LaunchTokenError::InvalidPlatformFee.into());
+            }
+            msg!(
+                "platform_fee_bps: {} -> {}",
+                config.platform_fee_bps,
+                platform_fee_bps
+            );
+            config.platform_fee_bps = platform_fee_bps;
+        }
+
+        config.serialize(&mut *config_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    pub fn close_project(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let manager_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let claim_pda_account = next_account_info(accounts_iter)?;
+        let token_vault_account = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+        let creator_account = next_account_info(accounts_iter)?;
+        let token_mint_account = next_account_info(accounts_iter)?;
+
+        let project_info = Self::load_project(program_id, project_account)?;
+        let payment_accounts = if project_info.payment_mint.is_some() {
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let payment_token_program = next_account_info(accounts_iter)?;
+            Some((payment_escrow_account, payment_token_program))
+        } else {
+            None
+        };
+        Self::check_authority(
+            program_id,
+            &project_info.manager,
+            manager_account,
+            accounts_iter.as_slice(),
+        )?;
+        if *creator_account.key != project_info.creator {
+            return Err(LaunchTokenError::IncorrectCreator.into());
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if !project_info.cancelled && clock.unix_timestamp <= project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchNotEnded.into());
+        }
+        if project_info.open_allocations > 0 {
+            msg!(
+                "{} buyers have not fully claimed or refunded",
+                project_info.open_allocations
+            );
+            return Err(LaunchTokenError::ObligationsOutstanding.into());
+        }
+
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
+        if vault_account.lamports() > (Rent::get()?).minimum_balance(0) {
+            msg!("proceeds are still in the vault");
+            return Err(LaunchTokenError::ObligationsOutstanding.into());
+        }
+        Self::check_claim_authority(
+            program_id,
+            project_account,
+            &project_info,
+            claim_pda_account,
+        )?;
+        Self::check_token_vault(&project_info, claim_pda_account, token_vault_account)?;
+        if *token_mint_account.key != project_info.token_mint {
+            return Err(LaunchTokenError::IncorrectTokenMint.into());
+        }
+        if *token_program.key != project_info.token_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if token_vault_account.lamports() > 0 {
+            if Self::unpack_token_account(token_vault_account)?.amount > 0 {
+                msg!("sale tokens are still in the token vault");
+                return Err(LaunchTokenError::ObligationsOutstanding.into());
+            }
+            // Token-2022 refuses to close an account still holding withheld fees.
+            if Self::withheld_transfer_fees(token_vault_account)? > 0 {
+                invoke(
+                    &transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                        token_program.key,
+                        token_mint_account.key,
+                        &[token_vault_account.key],
+                    )?,
+                    &[
+                        token_mint_account.clone(),
+                        token_vault_account.clone(),
+                        token_program.clone(),
+                    ],
+                )?;
+            }
+            invoke_signed(
+                &spl_token_2022::instruction::close_account(
+                    token_program.key,
+                    token_vault_account.key,
+                    creator_account.key,
+                    claim_pda_account.key,
+                    &[],
+                )?,
+                &[
+                    token_vault_account.clone(),
+                    creator_account.clone(),
+                    claim_pda_account.clone(),
+                    token_program.clone(),
+                ],
+                &[&[
+                    CLAIM_SEED_PREFIX.as_bytes(),
+                    project_account.key.as_ref(),
+                    &[project_info.claim_authority_bump],
+                ]],
+            )?;
+        }
+
+        if let Some((payment_escrow_account, payment_token_program)) = payment_accounts {
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                payment_token_program,
+            )?;
+            let escrowed =
+                spl_token::state::Account::unpack(&payment_escrow_account.try_borrow_data()?)?
+                    .amount;
+            if escrowed > 0 {
+                msg!("proceeds are still in the payment escrow");
+                return Err(LaunchTokenError::ObligationsOutstanding.into());
+            }
+            invoke_signed(
+                &spl_token::instruction::close_account(
+                    payment_token_program.key,
+                    payment_escrow_account.key,
+                    creator_account.key,
+                    vault_account.key,
+                    &[],
+                )?,
+                &[
+                    payment_escrow_account.clone(),
+                    creator_account.clone(),
+                    vault_account.clone(),
+                    payment_token_program.clone(),
+                ],
+                &[&[
+                    VAULT_SEED_PREFIX.as_bytes(),
+                    project_account.key.as_ref(),
+                    &[project_info.vault_bump],
+                ]],
+            )?;
+        }
+
+        if *claim_pda_account.owner == *program_id {
+            Self::close_account(claim_pda_account, creator_account)?;
+        }
+        Self::close_account(vault_account, creator_account)?;
+        Self::close_account(project_account, creator_account)?;
+
+        msg!(
+            "Project {} closed, rent returned to {}",
+            project_account.key,
+            creator_account.key
+        );
+        Ok(())
+    }
+
+    pub fn claim_referral_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let referrer_account = next_account_info(accounts_iter)?;
+        let clock_account = next_account_info(accounts_iter)?;
+        let referral_account = next_account_info(accounts_iter)?;
+        let project_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+
+        let mut project_info = Self::load_project(program_id, project_account)?;
+        Self::check_vault(program_id, project_account, &project_info, vault_account)?;
+        if !referrer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if referral_account.lamports() == 0 {
+            return Err(LaunchTokenError::NoReferralRewards.into());
+        }
+        if *referral_account.owner != *program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let referral = Referral::try_from_slice(&referral_account.try_borrow_data()?)?;
+        Self::check_referral(program_id, referral_account, &referral)?;
+        if referral.referrer != *referrer_account.key {
+            return Err(LaunchTokenError::PermissionForbidden.into());
+        }
+        if referral.project != *project_account.key {
+            return Err(LaunchTokenError::IncorrectProject.into());
+        }
+
+        let clock = Clock::from_account_info(clock_account)?;
+        if !project_info.cancelled && clock.unix_timestamp <= project_info.launch_end_time as i64 {
+            return Err(LaunchTokenError::LaunchNotEnded.into());
+        }
+
+        let reward = if project_info.is_sale_successful() {
+            project_info.referral_payout(referral.accrued)?
+        } else {
+            0
+        };
+        if reward == 0 {
+            msg!("no referral rewards are owed, closing the referral account");
+            return Self::close_account(referral_account, referrer_account);
+        }
+        if project_info.payment_mint.is_some() {
+            let payment_escrow_account = next_account_info(accounts_iter)?;
+            let referrer_token_account = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            Self::check_payment_escrow(
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                token_program,
+            )?;
+            Self::transfer_from_escrow(
+                project_account,
+                &project_info,
+                vault_account,
+                payment_escrow_account,
+                referrer_token_account,
+                token_program,
+                reward,
+            )?;
+        } else {
+            Self::transfer_lamports(vault_account, referrer_account, reward)?;
+        }
+        Self::close_account(referral_account, referrer_account)?;
+
+        project_info.referral_rewards_paid = project_info
+            .referral_rewards_paid
+            .checked_add(reward)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        project_info.serialize(&mut *project_account.data.borrow_mut())?;
+
+        msg!(
+            "Referrer {} claimed {} in referral rewards",
+            referrer_account.key,
+            reward
+        );
+        LaunchpadEvent::ReferralReward(ReferralRewardEvent {
+            project: *project_account.key,
+            referrer: *referrer_account.key,
+            amount: reward,
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Adds `reward` to `referrer`'s referral account, creating it on first use.
+    fn accrue_referral<'a>(
+        program_id: &Pubkey,
+        payer_account: &AccountInfo<'a>,
+        project_account: &AccountInfo<'a>,
+        referral_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        referrer: &Pubkey,
+        reward: u64,
+    ) -> ProgramResult {
+        if *referral_account.owner != *program_id {
+            let (referral_address, bump) =
+                find_referral_address(program_id, project_account.key, referrer);
+            if *referral_account.key != referral_address {
+                return Err(LaunchTokenError::InvalidPda.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                payer_account,
+                referral_account,
+                system_program,
+                Referral::SIZE,
+                &[
+                    Referral::SEED_PREFIX.as_bytes(),
+                    project_account.key.as_ref(),
+                    referrer.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            Referral {
+                project: *project_account.key,
+                referrer: *referrer,
+                bump,
+                ..Referral::default()
+            }
+            .serialize(&mut *referral_account.data.borrow_mut())?;
+        }
+        let mut referral = Referral::try_from_slice(&referral_account.try_borrow_data()?)?;
+        Self::check_referral(program_id, referral_account, &referral)?;
+        if referral.project != *project_account.key || referral.referrer != *referrer {
+            return Err(LaunchTokenError::InvalidReferrer.into());
+        }
+        referral.accrued = referral
+            .accrued
+            .checked_add(reward)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        referral.serialize(&mut *referral_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Checks that an ed25519 program instruction earlier in the transaction
+    /// verified the attestor's signature over an unexpired attestation for
+    /// `buyer` and `project`.
+    fn check_attestation(
+        project_info: &ProjectInfo,
+        buyer: &Pubkey,
+        project: &Pubkey,
+        instructions_sysvar: &AccountInfo,
+        now: i64,
+    ) -> ProgramResult {
+        let current = instructions::load_current_index_checked(instructions_sysvar)?;
+        let mut expired = false;
+        for index in 0..current {
+            let instruction =
+                instructions::load_instruction_at_checked(index as usize, instructions_sysvar)?;
+            if instruction.program_id != ed25519_program::ID {
+                continue;
+            }
+            for (signer, message) in attestation::verified_messages(&instruction.data) {
+                if signer != project_info.attestor {
+                    continue;
+                }
+                match attestation::expiry(message, buyer, project) {
+                    Some(expiry) if expiry > now => return Ok(()),
+                    Some(expiry) => {
+                        msg!("attestation expired at {}", expiry);
+                        expired = true;
+                    }
+                    None => {}
+                }
+            }
+        }
+        if expired {
+            return Err(LaunchTokenError::AttestationExpired.into());
+        }
+        msg!("no attestation by {} for {}", project_info.attestor, buyer);
+        Err(LaunchTokenError::AttestationRequired.into())
+    }
+
+    fn check_referral(
+        program_id: &Pubkey,
+        referral_account: &AccountInfo,
+        referral: &Referral,
+    ) -> ProgramResult {
+        Self::check_pda(
+            program_id,
+            referral_account,
+            &[
+                Referral::SEED_PREFIX.as_bytes(),
+                referral.project.as_ref(),
+                referral.referrer.as_ref(),
+                &[referral.bump],
+            ],
+        )
+    }
+
+    /// Checks that `authority_account` is the upgrade authority recorded in
+    /// this program's ProgramData account.
+    fn check_upgrade_authority(
+        program_id: &Pubkey,
+        program_data_account: &AccountInfo,
+        authority_account: &AccountInfo,
+    ) -> ProgramResult {
+        let (program_data_address, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        if *program_data_account.key != program_data_address {
+            return Err(LaunchTokenError::InvalidPda.into());
+        }
+        if *program_data_account.owner != bpf_loader_upgradeable::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let data = program_data_account.try_borrow_data()?;
+        let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        let metadata = data
+            .get(..metadata_len)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        match limited_deserialize(metadata, metadata_len as u64) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: Some(upgrade_authority),
+                ..
+            }) if upgrade_authority == *authority_account.key => Ok(()),
+            _ => {
+                msg!(
+                    "{} is not the program's upgrade authority",
+                    authority_account.key
+                );
+                Err(LaunchTokenError::PermissionForbidden.into())
+            }
+        }
+    }
+
+    /// Loads the platform config, checking that the account is owned by this
+    /// program and sits at the config PDA.
+    fn load_config(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+    ) -> Result<Config, ProgramError> {
+        if *config_account.owner != *program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let config = Config::try_from_slice(&config_account.try_borrow_data()?)?;
+        Self::check_pda(
+            program_id,
+            config_account,
+            &[Config::SEED_PREFIX.as_bytes(), &[config.bump]],
+        )?;
+        Ok(config)
+    }
+
+    /// Checks that `authority_account` is `authority` and approved the
+    /// instruction, either by signing or, for a `Multisig` account, through
+    /// `m` of its signers among `signer_accounts`.
+    fn check_authority(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        authority_account: &AccountInfo,
+        signer_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if *authority_account.key != *authority {
+            return Err(LaunchTokenError::PermissionForbidden.into());
+        }
+        if *authority_account.owner == *program_id && authority_account.data_len() == Multisig::SIZE
+        {
+            let multisig = Multisig::try_from_slice(&authority_account.try_borrow_data()?)?;
+            Self::check_pda(
+                program_id,
+                authority_account,
+                &[
+                    Multisig::SEED_PREFIX.as_bytes(),
+                    multisig.creator.as_ref(),
+                    &multisig.multisig_id.to_le_bytes(),
+                    &[multisig.bump],
+                ],
+            )?;
+            let approvals = multisig.approvals(signer_accounts);
+            if approvals < multisig.m as usize {
+                msg!("{} of {} multisig approvals", approvals, multisig.m);
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            return Ok(());
+        }
+        if !authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    /// Reads a sale token mint owned by the SPL Token or Token-2022 program.
+    fn unpack_token_mint(token_mint: &AccountInfo) -> Result<TokenMint, ProgramError> {
+        Ok(StateWithExtensions::<TokenMint>::unpack(&token_mint.try_borrow_data()?)?.base)
+    }
+
+    /// Reads a token account owned by the SPL Token or Token-2022 program.
+    fn unpack_token_account(token_account: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+        Ok(StateWithExtensions::<TokenAccount>::unpack(&token_account.try_borrow_data()?)?.base)
+    }
+
+    /// Token-2022 transfer fees withheld in a token account; `0` without any.
+    fn withheld_transfer_fees(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+        let data = token_account.try_borrow_data()?;
+        let account = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+        Ok(match account.get_extension::<TransferFeeAmount>() {
+            Ok(transfer_fee_amount) => u64::from(transfer_fee_amount.withheld_amount),
+            Err(_) => 0,
+        })
+    }
+
+    /// Returns the amount to send for the recipient to receive `net_amount`
+    /// once the mint's Token-2022 transfer fee, if any, is withheld.
+    ///
+    /// The fee is rounded up and capped per transfer, so paying an allocation
+    /// out in parts can cost more than paying it at once. The amount sent is
+    /// therefore capped at what `net_amount` adds to the gross-up of the
+    /// `unclaimed` allocation, which `ProjectInfo::claim_reserve` keeps back;
+    /// beyond that a claim may fall short of `net_amount` by a base unit.
+    fn gross_up_transfer_fee(
+        token_mint: &AccountInfo,
+        net_amount: u64,
+        unclaimed: u64,
+        epoch: u64,
+    ) -> Result<u64, ProgramError> {
+        let data = token_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<TokenMint>::unpack(&data)?;
+        let transfer_fee = match mint.get_extension::<TransferFeeConfig>() {
+            Ok(transfer_fee_config) => transfer_fee_config.get_epoch_fee(epoch),
+            Err(_) => return Ok(net_amount),
+        };
+        let gross_amount = transfer_fee
+            .calculate_pre_fee_amount(net_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let fee_bps = u16::from(transfer_fee.transfer_fee_basis_points);
+        let budget = gross_transfer_amount(unclaimed, fee_bps)?
+            - gross_transfer_amount(unclaimed - net_amount, fee_bps)?;
+        Ok(gross_amount.min(budget))
+    }
+
+    /// Highest of the mint's current and scheduled Token-2022 transfer fees, in
+    /// basis points; `0` without a transfer fee.
+    fn max_transfer_fee_bps(token_mint: &AccountInfo) -> Result<u16, ProgramError> {
+        let data = token_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<TokenMint>::unpack(&data)?;
+        Ok(match mint.get_extension::<TransferFeeConfig>() {
+            Ok(transfer_fee_config) => u16::from(
+                transfer_fee_config
+                    .older_transfer_fee
+                    .transfer_fee_basis_points,
+            )
+            .max(u16::from(
+                transfer_fee_config
+                    .newer_transfer_fee
+                    .transfer_fee_basis_points,
+            )),
+            Err(_) => 0,
+        })
+    }
+
+    /// Moves sale tokens with `transfer_checked` through the project's token program.
+    fn transfer_sale_tokens<'a>(
+        token_program: &AccountInfo<'a>,
+        source: &AccountInfo<'a>,
+        token_mint: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        amount: u64,
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let decimals = Self::unpack_token_mint(token_mint)?.decimals;
+        invoke_signed(
+            &spl_token_2022::instruction::transfer_checked(
+                token_program.key,
+                source.key,
+                token_mint.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            &[
+                source.clone(),
+                token_mint.clone(),
+                destination.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            signers_seeds,
+        )
+    }
+
+    /// Loads the project state, checking that the account is owned by this
+    /// program and sits at the PDA derived from its stored seeds and bump.
+    fn load_project(
+        program_id: &Pubkey,
+        project_account: &AccountInfo,
+    ) -> Result<ProjectInfo, ProgramError> {
+        if *project_account.owner != *program_id {
+            return Err(LaunchTokenError::IncorrectProject.into());
+        }
+        let project_info = ProjectInfo::deserialize(&mut &project_account.try_borrow_data()?[..])?;
+        Self::check_pda(
+            program_id,
+            project_account,
+            &[
+                ProjectInfo::SEED_PREFIX.as_bytes(),
+                project_info.creator.as_ref(),
+                &project_info.project_id.to_le_bytes(),
+                &[project_info.bump],
+            ],
+        )?;
+        Ok(project_info)
+    }
+
+    fn check_vault(
+        program_id: &Pubkey,
+        project_account: &AccountInfo,
+        project_info: &ProjectInfo,
+        vault_account: &AccountInfo,
+    ) -> ProgramResult {
+        Self::check_pda(
+            program_id,
+            vault_account,
+            &[
+                VAULT_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.vault_bump],
+            ],
+        )
+    }
+
+    fn check_claim_authority(
+        program_id: &Pubkey,
+        project_account: &AccountInfo,
+        project_info: &ProjectInfo,
+        claim_authority_account: &AccountInfo,
+    ) -> ProgramResult {
+        Self::check_pda(
+            program_id,
+            claim_authority_account,
+            &[
+                CLAIM_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.claim_authority_bump],
+            ],
+        )
+    }
+
+    fn check_token_vault(
+        project_info: &ProjectInfo,
+        claim_authority_account: &AccountInfo,
+        token_vault_account: &AccountInfo,
+    ) -> ProgramResult {
+        let token_vault =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                claim_authority_account.key,
+                &project_info.token_mint,
+                &project_info.token_program_id,
+            );
+        if *token_vault_account.key != token_vault {
+            return Err(LaunchTokenError::IncorrectTokenVault.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `payment_escrow_account` is the vault's token account for
+    /// the project's payment mint under `token_program`.
+    fn check_payment_escrow(
+        project_info: &ProjectInfo,
+        vault_account: &AccountInfo,
+        payment_escrow_account: &AccountInfo,
+        token_program: &AccountInfo,
+    ) -> ProgramResult {
+        let payment_mint = project_info
+            .payment_mint
+            .ok_or(LaunchTokenError::IncorrectPaymentMint)?;
+        if *token_program.key != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let payment_escrow =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                vault_account.key,
+                &payment_mint,
+                token_program.key,
+            );
+        if *payment_escrow_account.key != payment_escrow {
+            return Err(LaunchTokenError::IncorrectPaymentEscrow.into());
+        }
+        Ok(())
+    }
+
+    /// Moves payment tokens out of the escrow, signed by the vault PDA.
+    fn transfer_from_escrow<'a>(
+        project_account: &AccountInfo<'a>,
+        project_info: &ProjectInfo,
+        vault_account: &AccountInfo<'a>,
+        payment_escrow_account: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                payment_escrow_account.key,
+                destination.key,
+                vault_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                payment_escrow_account.clone(),
+                destination.clone(),
+                vault_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[
+                VAULT_SEED_PREFIX.as_bytes(),
+                project_account.key.as_ref(),
+                &[project_info.vault_bump],
+            ]],
+        )
+    }
+
+    fn check_mint_info(
+        program_id: &Pubkey,
+        mint_account: &AccountInfo,
+        mint_info: &MintInfo,
+    ) -> ProgramResult {
+        Self::check_pda(
+            program_id,
+            mint_account,
+            &[
+                MintInfo::SEED_PREFIX.as_bytes(),
+                mint_info.project.as_ref(),
+                mint_info.buyer.as_ref(),
+                &[mint_info.bump],
+            ],
+        )
+    }
+
+    /// Fails with `InvalidPda` unless `account` is the address derived from `seeds`.
+    fn check_pda(program_id: &Pubkey, account: &AccountInfo, seeds: &[&[u8]]) -> ProgramResult {
+        match Pubkey::create_program_address(seeds, program_id) {
+            Ok(address) if address == *account.key => Ok(()),
+            _ => {
+                msg!("{} is not the expected PDA", account.key);
+                Err(LaunchTokenError::InvalidPda.into())
+            }
+        }
+    }
+
+    /// Creates the PDA `account` with `space` bytes, owned by this program.
+    ///
+    /// Anyone can send lamports to a PDA before it is created, which makes
+    /// `create_account` fail. A funded address is topped up to rent exemption
+    /// and then allocated and assigned instead.
+    fn create_pda_account<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        space: usize,
+        seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let lamports_required = (Rent::get()?).minimum_balance(space);
+        if account.lamports() == 0 {
+            return invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    account.key,
+                    lamports_required,
+                    space as u64,
+                    program_id,
+                ),
+                &[payer.clone(), account.clone(), system_program.clone()],
+                &[seeds],
+            );
+        }
+        let top_up = lamports_required.saturating_sub(account.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, top_up),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(account.key, space as u64),
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(account.key, program_id),
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )
+    }
+
+    /// Moves lamports out of an account owned by this program.
+    fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+        let from_lamports = from
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        let to_lamports = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **from.try_borrow_mut_lamports()? = from_lamports;
+        **to.try_borrow_mut_lamports()? = to_lamports;
+        Ok(())
+    }
+
+    /// Drains a program-owned account into `destination` and wipes its data.
+    fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+        Self::transfer_lamports(account, destination, account.lamports())?;
+        account.try_borrow_mut_data()?.fill(0);
         Ok(())
     }
 }
@@ -1,16 +1,674 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::LaunchTokenError;
+
+/// Basis points making up 100%.
+pub const MAX_BPS: u16 = 10_000;
+
+/// Decimals of lamports relative to SOL.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Most rounds a project may run.
+pub const MAX_ROUNDS: usize = 4;
+
+/// Amount to send for the recipient to receive `amount` under a transfer fee
+/// of `fee_bps`, rounded up and ignoring any maximum fee. However a payout is
+/// split into transfers, each one grossed up this way on its cumulative total
+/// costs no more than this in all.
+pub fn gross_transfer_amount(amount: u64, fee_bps: u16) -> Result<u64, ProgramError> {
+    if fee_bps == 0 {
+        return Ok(amount);
+    }
+    let kept = MAX_BPS
+        .checked_sub(fee_bps)
+        .filter(|kept| *kept > 0)
+        .ok_or(ProgramError::ArithmeticOverflow)? as u128;
+    let gross = (amount as u128 * MAX_BPS as u128).div_ceil(kept);
+    u64::try_from(gross).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// One round of a multi-round sale, such as a seed, private or public round.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct SaleRound {
+    /// The round accepts contributions from `start_time` until just before `end_time`.
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Price of the round, like `ProjectInfo::token_price_numerator / token_price_denominator`.
+    pub token_price_numerator: u64,
+    pub token_price_denominator: u64,
+    /// Maximum amount the round accepts; `0` means uncapped.
+    pub cap: u64,
+    /// Amount contributed during the round; ignored in instruction arguments.
+    pub raised: u64,
+    /// Merkle root of the round's allowlist; all zeroes opens the round to everyone.
+    pub allowlist_root: [u8; 32],
+}
+
+impl SaleRound {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8 + 8 + 32;
+
+    /// Returns whether `now` falls inside the round.
+    pub fn is_active(&self, now: i64) -> bool {
+        now >= self.start_time as i64 && now < self.end_time as i64
+    }
+}
 
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
 pub struct ProjectInfo {
+    /// Current manager: a wallet, or a `Multisig` account requiring M-of-N approvals.
     pub manager: Pubkey,
-    pub token_price: u64,
+    /// Manager that created the project, whose key seeds the project PDA.
+    pub creator: Pubkey,
+    /// Manager proposed by `ProposeManager`; all zeroes when none is pending.
+    pub pending_manager: Pubkey,
+    /// Price in whole tokens per whole payment unit, as `token_price_numerator / token_price_denominator`.
+    pub token_price_numerator: u64,
+    pub token_price_denominator: u64,
     pub seller_account: Pubkey,
     pub launch_start_time: u64,
     pub launch_end_time: u64,
     pub claim_start_time: u64,
+    /// Deadline for claims; `0` means tokens can be claimed indefinitely.
+    pub claim_end_time: u64,
     pub token_program_id: Pubkey,
+    pub token_mint: Pubkey,
+    /// Sale tokens deposited into the token vault and not reclaimed by the seller.
+    pub tokens_for_sale: u64,
+    /// Decimals of `token_mint`, recorded on deposit.
+    pub token_decimals: u8,
+    pub project_id: u64,
+    /// Minimum amount the sale must raise to succeed; below it buyers are refunded.
+    ///
+    /// Caps, contribution limits and `total_raised` are in lamports, or in base
+    /// units of `payment_mint` for sales paid in SPL tokens.
+    pub soft_cap: u64,
+    /// Maximum amount the sale accepts; `0` means uncapped. In overflow mode
+    /// it is the amount the sale keeps instead, and must be set.
+    pub hard_cap: u64,
+    pub total_raised: u64,
+    /// Minimum amount a wallet must have contributed in total after each `Mint`.
+    pub min_contribution: u64,
+    /// Maximum amount a wallet may contribute in total; `0` means unlimited.
+    pub max_contribution_per_wallet: u64,
+    /// Merkle root of the presale allowlist; all zeroes disables the allowlist phase.
+    pub allowlist_root: [u8; 32],
+    /// Start of the allowlist phase, which must end before the public sale starts.
+    pub allowlist_start_time: u64,
+    pub allowlist_end_time: u64,
+    /// Share of each allocation, in basis points, released at `claim_start_time`.
+    pub tge_unlock_bps: u16,
+    /// Seconds after `claim_start_time` before linear vesting begins.
+    pub vesting_cliff: u64,
+    /// Seconds over which the remainder vests linearly once the cliff has passed.
+    pub vesting_duration: u64,
+    /// Canonical bumps of the project, claim authority and vault PDAs.
+    pub bump: u8,
+    pub claim_authority_bump: u8,
+    pub vault_bump: u8,
+    /// Set by the manager to halt contributions and claims until unpaused.
+    pub paused: bool,
+    /// Set by `CancelSale`; buyers can then refund their contributions.
+    pub cancelled: bool,
+    /// Platform fee in basis points, copied from `Config` when the project is created.
+    pub platform_fee_bps: u16,
+    /// Platform fees sent to the treasury out of withdrawn proceeds.
+    pub platform_fee_paid: u64,
+    /// Decimals of the payment unit: `SOL_DECIMALS`, or those of `payment_mint`.
+    pub payment_decimals: u8,
+    /// SPL token the sale is paid in; `None` for sales paid in SOL.
+    pub payment_mint: Option<Pubkey>,
+    /// Mint info accounts not yet closed by a final claim or a refund.
+    pub open_allocations: u64,
+    /// Accept contributions beyond the hard cap and allocate pro rata, refunding
+    /// each buyer's excess when they claim.
+    pub overflow: bool,
+    /// Excess contributions refunded to buyers of an oversubscribed sale, or
+    /// to bidders in a Dutch auction.
+    pub excess_refunded: u64,
+    /// Dutch auction price denominator at `launch_start_time`, decaying linearly
+    /// to `token_price_denominator` by `launch_end_time`; `0` disables the auction.
+    pub auction_start_denominator: u64,
+    /// Lowest price denominator bid in the auction, at which every bidder settles.
+    /// Extending `launch_end_time` raises the price again, so it is not
+    /// necessarily that of the latest bid.
+    pub auction_clearing_denominator: u64,
+    /// Tokens bought in the auction, at the prices bid.
+    pub auction_tokens_sold: u64,
+    /// Number of entries of `rounds` in use; `0` runs a single sale at the project price.
+    pub round_count: u8,
+    /// Rounds in chronological order, within the launch window.
+    pub rounds: [SaleRound; MAX_ROUNDS],
+    /// Share of referred contributions, in basis points, paid to the referrer
+    /// out of the proceeds.
+    pub referral_bps: u16,
+    /// Referral rewards accrued across all referrers, before any pro rata scaling.
+    pub referral_rewards: u64,
+    /// Referral rewards paid out to referrers.
+    pub referral_rewards_paid: u64,
+    /// Key whose ed25519 attestation each `Mint` needs; all zeroes lets anyone contribute.
+    pub attestor: Pubkey,
+    /// Sale tokens released to buyers, net of transfer fees.
+    pub tokens_claimed: u64,
+    /// Highest Token-2022 transfer fee of `token_mint` seen on deposit, in basis points.
+    pub transfer_fee_bps: u16,
+}
+
+impl ProjectInfo {
+    pub const SIZE: usize = 32 // manager
+        + 32 // creator
+        + 32 // pending_manager
+        + 8 // token_price_numerator
+        + 8 // token_price_denominator
+        + 32 // seller_account
+        + 8 // launch_start_time
+        + 8 // launch_end_time
+        + 8 // claim_start_time
+        + 8 // claim_end_time
+        + 32 // token_program_id
+        + 32 // token_mint
+        + 8 // tokens_for_sale
+        + 1 // token_decimals
+        + 8 // project_id
+        + 8 // soft_cap
+        + 8 // hard_cap
+        + 8 // total_raised
+        + 8 // min_contribution
+        + 8 // max_contribution_per_wallet
+        + 32 // allowlist_root
+        + 8 // allowlist_start_time
+        + 8 // allowlist_end_time
+        + 2 // tge_unlock_bps
+        + 8 // vesting_cliff
+        + 8 // vesting_duration
+        + 1 // bump
+        + 1 // claim_authority_bump
+        + 1 // vault_bump
+        + 1 // paused
+        + 1 // cancelled
+        + 2 // platform_fee_bps
+        + 8 // platform_fee_paid
+        + 1 // payment_decimals
+        + 1 + 32 // payment_mint
+        + 8 // open_allocations
+        + 1 // overflow
+        + 8 // excess_refunded
+        + 8 // auction_start_denominator
+        + 8 // auction_clearing_denominator
+        + 8 // auction_tokens_sold
+        + 1 // round_count
+        + SaleRound::SIZE * MAX_ROUNDS // rounds
+        + 2 // referral_bps
+        + 8 // referral_rewards
+        + 8 // referral_rewards_paid
+        + 32 // attestor
+        + 8 // tokens_claimed
+        + 2; // transfer_fee_bps
+
+    pub const SEED_PREFIX: &'static str = "octo-project";
+
+    /// Returns whether `launch_start_time < launch_end_time <= claim_start_time`
+    /// and, when a claim deadline is set, `claim_start_time < claim_end_time`.
+    pub fn is_time_range_valid(&self) -> bool {
+        self.launch_start_time < self.launch_end_time
+            && self.launch_end_time <= self.claim_start_time
+            && (self.claim_end_time == 0 || self.claim_start_time < self.claim_end_time)
+    }
+
+    /// Returns whether the soft cap does not exceed a configured hard cap, which
+    /// overflow mode requires.
+    pub fn are_caps_valid(&self) -> bool {
+        if self.hard_cap == 0 {
+            !self.overflow
+        } else {
+            self.soft_cap <= self.hard_cap
+        }
+    }
+
+    /// Returns whether the per-wallet minimum does not exceed a configured maximum.
+    pub fn are_contribution_limits_valid(&self) -> bool {
+        self.max_contribution_per_wallet == 0
+            || self.min_contribution <= self.max_contribution_per_wallet
+    }
+
+    /// Returns whether contributions need an attestation.
+    pub fn has_attestor(&self) -> bool {
+        self.attestor != Pubkey::default()
+    }
+
+    /// Returns whether an allowlist phase is configured.
+    pub fn has_allowlist(&self) -> bool {
+        self.allowlist_root != [0; 32]
+    }
+
+    /// Returns whether a configured allowlist phase ends before the public sale starts.
+    pub fn is_allowlist_window_valid(&self) -> bool {
+        !self.has_allowlist()
+            || (self.allowlist_start_time < self.allowlist_end_time
+                && self.allowlist_end_time <= self.launch_start_time)
+    }
+
+    /// Returns whether `now` falls inside the allowlist phase.
+    pub fn is_allowlist_phase(&self, now: i64) -> bool {
+        self.has_allowlist()
+            && now >= self.allowlist_start_time as i64
+            && now < self.allowlist_end_time as i64
+    }
+
+    /// Time at which the first phase of the sale opens.
+    pub fn sale_start_time(&self) -> u64 {
+        if self.has_allowlist() {
+            self.allowlist_start_time
+        } else {
+            self.launch_start_time
+        }
+    }
+
+    /// Returns whether the TGE unlock is at most 100%.
+    pub fn is_vesting_schedule_valid(&self) -> bool {
+        self.tge_unlock_bps <= MAX_BPS
+    }
+
+    /// Portion of `total` tokens vested at `now`.
+    pub fn vested_amount(&self, total: u64, now: i64) -> u64 {
+        let claim_start = self.claim_start_time as i64;
+        if now < claim_start {
+            return 0;
+        }
+        let total = total as u128;
+        let unlocked = total * self.tge_unlock_bps as u128 / MAX_BPS as u128;
+        let cliff_end = claim_start.saturating_add(self.vesting_cliff as i64);
+        if now < cliff_end {
+            return unlocked as u64;
+        }
+        let elapsed = (now - cliff_end) as u128;
+        if self.vesting_duration == 0 || elapsed >= self.vesting_duration as u128 {
+            return total as u64;
+        }
+        let linear = (total - unlocked) * elapsed / self.vesting_duration as u128;
+        (unlocked + linear) as u64
+    }
+
+    /// Returns whether the price has a nonzero numerator and denominator.
+    pub fn is_token_price_valid(&self) -> bool {
+        self.token_price_numerator > 0 && self.token_price_denominator > 0
+    }
+
+    /// Converts a contribution in payment units into base units of a mint with `decimals`.
+    pub fn token_amount(&self, contribution: u64, decimals: u8) -> Result<u64, ProgramError> {
+        self.token_amount_at(
+            contribution,
+            decimals,
+            self.token_price_numerator,
+            self.token_price_denominator,
+        )
+    }
+
+    /// Converts a contribution like `token_amount`, at `price_numerator / price_denominator`.
+    pub fn token_amount_at(
+        &self,
+        contribution: u64,
+        decimals: u8,
+        price_numerator: u64,
+        price_denominator: u64,
+    ) -> Result<u64, ProgramError> {
+        let scale = 10u128
+            .checked_pow(decimals as u32)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let payment_scale = 10u128
+            .checked_pow(self.payment_decimals as u32)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let denominator = (price_denominator as u128)
+            .checked_mul(payment_scale)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let amount = (contribution as u128)
+            .checked_mul(price_numerator as u128)
+            .and_then(|amount| amount.checked_mul(scale))
+            .and_then(|amount| amount.checked_div(denominator))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        u64::try_from(amount).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// Returns the platform's cut of `proceeds`.
+    pub fn platform_fee(&self, proceeds: u64) -> u64 {
+        (proceeds as u128 * self.platform_fee_bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    /// Part of `total_raised` the sale keeps; in overflow mode anything above
+    /// the hard cap goes back to the buyers.
+    pub fn accepted_total(&self, total_raised: u64) -> u64 {
+        if self.overflow {
+            total_raised.min(self.hard_cap)
+        } else {
+            total_raised
+        }
+    }
+
+    /// Returns whether an overflow sale raised more than its hard cap.
+    pub fn is_oversubscribed(&self) -> bool {
+        self.overflow && self.total_raised > self.hard_cap
+    }
+
+    /// Part of a buyer's contribution refunded because the sale is
+    /// oversubscribed, or because the auction cleared below their bids.
+    pub fn excess_contribution(&self, mint_info: &MintInfo) -> Result<u64, ProgramError> {
+        if self.is_dutch_auction() {
+            let cost = self.auction_cost(mint_info.auction_tokens)?;
+            return Ok(mint_info.amount.saturating_sub(cost));
+        }
+        if !self.is_oversubscribed() {
+            return Ok(0);
+        }
+        let excess = self.total_raised - self.hard_cap;
+        Ok((mint_info.amount as u128 * excess as u128 / self.total_raised as u128) as u64)
+    }
+
+    /// Tokens bought by a buyer, in base units of a mint with `decimals`. Scaled
+    /// down pro rata when the sale is oversubscribed.
+    pub fn allocation(&self, mint_info: &MintInfo, decimals: u8) -> Result<u64, ProgramError> {
+        if self.is_dutch_auction() {
+            return Ok(mint_info.auction_tokens);
+        }
+        if self.has_rounds() {
+            return self.rounds().iter().zip(mint_info.round_amounts).try_fold(
+                0u64,
+                |total, (round, amount)| {
+                    let tokens = self.token_amount_at(
+                        amount,
+                        decimals,
+                        round.token_price_numerator,
+                        round.token_price_denominator,
+                    )?;
+                    total
+                        .checked_add(tokens)
+                        .ok_or(ProgramError::ArithmeticOverflow)
+                },
+            );
+        }
+        if !self.is_oversubscribed() {
+            return self.token_amount(mint_info.amount, decimals);
+        }
+        let sold = self.token_amount(self.hard_cap, decimals)?;
+        Ok((sold as u128 * mint_info.amount as u128 / self.total_raised as u128) as u64)
+    }
+
+    /// Part of `total_raised` owed back to buyers of an oversubscribed sale or
+    /// to auction bidders.
+    pub fn excess_total(&self) -> Result<u64, ProgramError> {
+        if self.is_dutch_auction() {
+            return Ok(self
+                .total_raised
+                .saturating_sub(self.auction_cost(self.auction_tokens_sold)?));
+        }
+        Ok(self.total_raised - self.accepted_total(self.total_raised))
+    }
+
+    /// Excess contributions still held for buyers who have not claimed yet.
+    pub fn outstanding_excess(&self) -> Result<u64, ProgramError> {
+        if self.open_allocations == 0 {
+            return Ok(0);
+        }
+        Ok(self.excess_total()?.saturating_sub(self.excess_refunded))
+    }
+
+    /// Returns whether referral rewards and the platform fee together take at
+    /// most 100% of the proceeds.
+    pub fn is_referral_rate_valid(&self) -> bool {
+        self.referral_bps as u32 + self.platform_fee_bps as u32 <= MAX_BPS as u32
+    }
+
+    /// Check every field constraint, logging the first one violated.
+    pub fn validate(&self) -> ProgramResult {
+        if !self.is_token_price_valid() {
+            msg!("token_price_numerator and token_price_denominator must be nonzero");
+            return Err(LaunchTokenError::InvalidTokenPrice.into());
+        }
+
+        if !self.is_time_range_valid() {
+            msg!("launch_start_time < launch_end_time <= claim_start_time < claim_end_time is required");
+            return Err(LaunchTokenError::InvalidTimeRange.into());
+        }
+
+        if !self.are_caps_valid() {
+            msg!("soft_cap <= hard_cap is required, and overflow mode needs a hard cap");
+            return Err(LaunchTokenError::InvalidCaps.into());
+        }
+
+        if !self.are_contribution_limits_valid() {
+            msg!("min_contribution <= max_contribution_per_wallet is required");
+            return Err(LaunchTokenError::InvalidContributionLimits.into());
+        }
+
+        if !self.is_allowlist_window_valid() {
+            msg!("allowlist_start_time < allowlist_end_time <= launch_start_time is required");
+            return Err(LaunchTokenError::InvalidAllowlistWindow.into());
+        }
+
+        if !self.is_vesting_schedule_valid() {
+            msg!("tge_unlock_bps must not exceed {}", MAX_BPS);
+            return Err(LaunchTokenError::InvalidVestingSchedule.into());
+        }
+
+        if !self.is_auction_valid() {
+            msg!(
+                "auction_start_denominator > token_price_denominator is required, without overflow"
+            );
+            return Err(LaunchTokenError::InvalidAuction.into());
+        }
+
+        if !self.are_rounds_valid() {
+            msg!(
+                "up to {} ordered, priced rounds inside the launch window are required, without an allowlist phase, overflow or auction",
+                MAX_ROUNDS
+            );
+            return Err(LaunchTokenError::InvalidRounds.into());
+        }
+
+        if !self.is_referral_rate_valid() {
+            msg!(
+                "referral_bps + platform fee {} bps must not exceed {}",
+                self.platform_fee_bps,
+                MAX_BPS
+            );
+            return Err(LaunchTokenError::InvalidReferralRate.into());
+        }
+        Ok(())
+    }
+
+    /// Referral reward earned on `contribution`.
+    pub fn referral_reward(&self, contribution: u64) -> u64 {
+        (contribution as u128 * self.referral_bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    /// Part of `accrued` referral rewards paid out, scaled down like the
+    /// contributions when some of them are refunded as excess.
+    pub fn referral_payout(&self, accrued: u64) -> Result<u64, ProgramError> {
+        if self.total_raised == 0 {
+            return Ok(0);
+        }
+        let kept = self.total_raised - self.excess_total()?;
+        Ok((accrued as u128 * kept as u128 / self.total_raised as u128) as u64)
+    }
+
+    /// Referral rewards still held for referrers who have not claimed yet.
+    pub fn outstanding_referral_rewards(&self) -> Result<u64, ProgramError> {
+        Ok(self
+            .referral_payout(self.referral_rewards)?
+            .saturating_sub(self.referral_rewards_paid))
+    }
+
+    /// Tokens owed to buyers for everything raised so far, in base units of a
+    /// mint with `decimals`.
+    pub fn tokens_sold(&self, decimals: u8) -> Result<u64, ProgramError> {
+        if self.is_dutch_auction() {
+            return Ok(self.auction_tokens_sold);
+        }
+        if self.has_rounds() {
+            return self.rounds().iter().try_fold(0u64, |total, round| {
+                let tokens = self.token_amount_at(
+                    round.raised,
+                    decimals,
+                    round.token_price_numerator,
+                    round.token_price_denominator,
+                )?;
+                total
+                    .checked_add(tokens)
+                    .ok_or(ProgramError::ArithmeticOverflow)
+            });
+        }
+        self.token_amount(self.accepted_total(self.total_raised), decimals)
+    }
+
+    /// Sale tokens the vault must keep for the claims still to come, with
+    /// transfer fees at `fee_bps` and a base unit of rounding per allocation.
+    pub fn claim_reserve(&self, fee_bps: u16) -> Result<u64, ProgramError> {
+        let unclaimed = self
+            .tokens_sold(self.token_decimals)?
+            .saturating_sub(self.tokens_claimed);
+        let rounding = if fee_bps == 0 {
+            0
+        } else {
+            self.open_allocations
+        };
+        gross_transfer_amount(unclaimed, fee_bps)?
+            .checked_add(rounding)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Returns whether the sale runs in rounds.
+    pub fn has_rounds(&self) -> bool {
+        self.round_count > 0
+    }
+
+    /// Rounds in use.
+    pub fn rounds(&self) -> &[SaleRound] {
+        &self.rounds[..self.round_count as usize]
+    }
+
+    /// Index of the round open at `now`, if any.
+    pub fn active_round(&self, now: i64) -> Option<usize> {
+        self.rounds().iter().position(|round| round.is_active(now))
+    }
+
+    /// Replaces the rounds with `rounds`, with nothing raised yet. Returns
+    /// `false` if there are more than `MAX_ROUNDS`.
+    pub fn set_rounds(&mut self, rounds: &[SaleRound]) -> bool {
+        if rounds.len() > MAX_ROUNDS {
+            return false;
+        }
+        self.rounds = Default::default();
+        for (slot, round) in self.rounds.iter_mut().zip(rounds) {
+            *slot = SaleRound {
+                raised: 0,
+                ..*round
+            };
+        }
+        self.round_count = rounds.len() as u8;
+        true
+    }
+
+    /// Returns whether configured rounds are priced, ordered without overlap,
+    /// inside the launch window, and not combined with the presale allowlist,
+    /// overflow mode or a Dutch auction.
+    pub fn are_rounds_valid(&self) -> bool {
+        if !self.has_rounds() {
+            return true;
+        }
+        let rounds = self.rounds();
+        !self.has_allowlist()
+            && !self.overflow
+            && !self.is_dutch_auction()
+            && rounds.iter().all(|round| {
+                round.start_time < round.end_time
+                    && round.token_price_numerator > 0
+                    && round.token_price_denominator > 0
+            })
+            && rounds
+                .windows(2)
+                .all(|pair| pair[0].end_time <= pair[1].start_time)
+            && rounds[0].start_time >= self.launch_start_time
+            && rounds[rounds.len() - 1].end_time <= self.launch_end_time
+    }
+
+    /// Returns whether the sale is a Dutch auction.
+    pub fn is_dutch_auction(&self) -> bool {
+        self.auction_start_denominator != 0
+    }
+
+    /// Returns whether a configured auction starts above its floor price and
+    /// does not also use overflow mode.
+    pub fn is_auction_valid(&self) -> bool {
+        !self.is_dutch_auction()
+            || (self.auction_start_denominator > self.token_price_denominator && !self.overflow)
+    }
+
+    /// Price denominator in effect at `now`; fixed at `token_price_denominator`
+    /// unless the sale is a Dutch auction.
+    pub fn auction_price_denominator(&self, now: i64) -> u64 {
+        if !self.is_dutch_auction() {
+            return self.token_price_denominator;
+        }
+        let start = self.launch_start_time as i64;
+        if now <= start {
+            return self.auction_start_denominator;
+        }
+        let elapsed = (now - start) as u128;
+        let duration = (self.launch_end_time - self.launch_start_time) as u128;
+        if elapsed >= duration {
+            return self.token_price_denominator;
+        }
+        let range = (self.auction_start_denominator - self.token_price_denominator) as u128;
+        self.auction_start_denominator - (range * elapsed / duration) as u64
+    }
+
+    /// Records an auction bid of `amount` at `now` and returns the tokens it
+    /// buys. The clearing price drops to the bid price if that is lower.
+    pub fn place_auction_bid(&mut self, amount: u64, now: i64) -> Result<u64, ProgramError> {
+        let price_denominator = self.auction_price_denominator(now);
+        let tokens = self.token_amount_at(
+            amount,
+            self.token_decimals,
+            self.token_price_numerator,
+            price_denominator,
+        )?;
+        self.auction_clearing_denominator =
+            self.auction_clearing_denominator.min(price_denominator);
+        self.auction_tokens_sold = self
+            .auction_tokens_sold
+            .checked_add(tokens)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(tokens)
+    }
+
+    /// Payment owed for `tokens` at the auction clearing price, rounded up.
+    pub fn auction_cost(&self, tokens: u64) -> Result<u64, ProgramError> {
+        let scale = 10u128
+            .checked_pow(self.token_decimals as u32)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let payment_scale = 10u128
+            .checked_pow(self.payment_decimals as u32)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let denominator = (self.token_price_numerator as u128)
+            .checked_mul(scale)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let cost = (tokens as u128)
+            .checked_mul(self.auction_clearing_denominator as u128)
+            .and_then(|cost| cost.checked_mul(payment_scale))
+            .and_then(|cost| cost.checked_add(denominator - 1))
+            .and_then(|cost| cost.checked_div(denominator))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        u64::try_from(cost).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// Returns whether the sale has reached its soft cap and was not cancelled.
+    pub fn is_sale_successful(&self) -> bool {
+        !self.cancelled && self.total_raised >= self.soft_cap
+    }
 }
 
 #[repr(C)]
@@ -19,11 +677,20 @@ pub struct MintInfo {
     pub buyer: Pubkey,
     pub project: Pubkey,
     pub amount: u64,
-    pub is_claimed: bool,
+    /// Tokens already released to the buyer.
+    pub claimed_amount: u64,
+    pub bump: u8,
+    /// Excess contribution refunded to the buyer of an oversubscribed sale or
+    /// a Dutch auction.
+    pub refunded_amount: u64,
+    /// Tokens bought in a Dutch auction, at the prices bid.
+    pub auction_tokens: u64,
+    /// Contribution made in each round of a multi-round sale.
+    pub round_amounts: [u64; MAX_ROUNDS],
 }
 
 impl MintInfo {
-    pub const SIZE: usize = 32 + 32 + 8 + 1;
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1 + 8 + 8 + 8 * MAX_ROUNDS;
 
     pub const SEED_PREFIX: &'static str = "octu";
 
@@ -35,3 +702,541 @@ impl MintInfo {
     //     self.page_visits += 1;
     // }
 }
+
+/// Referral rewards a referrer accrued on the contributions they brought to a project.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Referral {
+    pub project: Pubkey,
+    pub referrer: Pubkey,
+    /// Rewards accrued, before any pro rata scaling.
+    pub accrued: u64,
+    pub bump: u8,
+}
+
+impl Referral {
+    pub const SIZE: usize = 32 + 32 + 8 + 1;
+
+    pub const SEED_PREFIX: &'static str = "octo-referral";
+}
+
+/// Platform-wide settings shared by every project.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Config {
+    /// Key allowed to change the config; may be a `Multisig` account.
+    pub admin: Pubkey,
+    /// Wallet receiving platform fees.
+    pub treasury: Pubkey,
+    /// Share of each raise, in basis points, taken as the platform fee.
+    pub platform_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const SIZE: usize = 32 + 32 + 2 + 1;
+
+    pub const SEED_PREFIX: &'static str = "octo-config";
+}
+
+/// Most signers a `Multisig` may have.
+pub const MAX_SIGNERS: usize = 11;
+
+/// M-of-N signer set that can act as a project manager.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Multisig {
+    pub creator: Pubkey,
+    pub multisig_id: u64,
+    pub bump: u8,
+    /// Number of signers whose approval is required.
+    pub m: u8,
+    /// Number of valid entries in `signers`.
+    pub n: u8,
+    pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl Multisig {
+    pub const SIZE: usize = 32 + 8 + 1 + 1 + 1 + 32 * MAX_SIGNERS;
+
+    pub const SEED_PREFIX: &'static str = "octo-multisig";
+
+    /// Counts the distinct signers of this multisig that signed among `accounts`.
+    pub fn approvals(&self, accounts: &[AccountInfo]) -> usize {
+        self.signers[..self.n as usize]
+            .iter()
+            .filter(|signer| {
+                accounts
+                    .iter()
+                    .any(|account| account.is_signer && account.key == *signer)
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAIM_START: i64 = 1_000;
+
+    fn vesting(tge_unlock_bps: u16, vesting_cliff: u64, vesting_duration: u64) -> ProjectInfo {
+        ProjectInfo {
+            claim_start_time: CLAIM_START as u64,
+            tge_unlock_bps,
+            vesting_cliff,
+            vesting_duration,
+            ..ProjectInfo::default()
+        }
+    }
+
+    #[test]
+    fn nothing_vests_before_claim_start() {
+        let project = vesting(MAX_BPS, 0, 0);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START - 1), 0);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START), 1_000);
+    }
+
+    #[test]
+    fn without_schedule_everything_vests_at_claim_start() {
+        let project = vesting(0, 0, 0);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START), 1_000);
+    }
+
+    #[test]
+    fn tge_unlock_then_cliff_then_linear() {
+        let project = vesting(2_000, 100, 400);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START), 200);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 99), 200);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 100), 200);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 200), 400);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 399), 798);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 500), 1_000);
+        assert_eq!(project.vested_amount(1_000, i64::MAX), 1_000);
+    }
+
+    #[test]
+    fn cliff_without_linear_vesting_releases_the_rest_at_once() {
+        let project = vesting(2_500, 100, 0);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 99), 250);
+        assert_eq!(project.vested_amount(1_000, CLAIM_START + 100), 1_000);
+    }
+
+    #[test]
+    fn vesting_does_not_overflow_on_large_allocations() {
+        let project = vesting(1, 0, u64::MAX);
+        assert_eq!(
+            project.vested_amount(u64::MAX, CLAIM_START),
+            u64::MAX / 10_000
+        );
+        assert!(project.vested_amount(u64::MAX, i64::MAX) < u64::MAX);
+    }
+
+    fn priced(numerator: u64, denominator: u64, payment_decimals: u8) -> ProjectInfo {
+        ProjectInfo {
+            token_price_numerator: numerator,
+            token_price_denominator: denominator,
+            payment_decimals,
+            ..ProjectInfo::default()
+        }
+    }
+
+    #[test]
+    fn token_amount_scales_between_decimals() {
+        let project = priced(1, 1, SOL_DECIMALS);
+        assert_eq!(project.token_amount(1_000_000_000, 6).unwrap(), 1_000_000);
+        assert_eq!(
+            project.token_amount(1_000_000_000, 9).unwrap(),
+            1_000_000_000
+        );
+        assert_eq!(project.token_amount(1_000_000_000, 0).unwrap(), 1);
+
+        let project = priced(1, 1, 6);
+        assert_eq!(project.token_amount(2_500_000, 9).unwrap(), 2_500_000_000);
+    }
+
+    #[test]
+    fn token_amount_applies_the_price() {
+        let project = priced(3, 2, SOL_DECIMALS);
+        assert_eq!(project.token_amount(1_000_000_000, 6).unwrap(), 1_500_000);
+        let project = priced(1, 4, SOL_DECIMALS);
+        assert_eq!(project.token_amount(1_000_000_000, 6).unwrap(), 250_000);
+    }
+
+    #[test]
+    fn token_amount_rounds_down() {
+        let project = priced(1, 1, SOL_DECIMALS);
+        assert_eq!(project.token_amount(999, 6).unwrap(), 0);
+        assert_eq!(project.token_amount(1_999, 6).unwrap(), 1);
+        let project = priced(1, 3, SOL_DECIMALS);
+        assert_eq!(project.token_amount(1_000_000_000, 6).unwrap(), 333_333);
+    }
+
+    #[test]
+    fn token_amount_reports_overflow() {
+        let project = priced(u64::MAX, 1, 0);
+        assert_eq!(
+            project.token_amount(u64::MAX, 9),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            priced(1, 1, 0).token_amount(1, 40),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            priced(1, 0, 0).token_amount(1, 0),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn gross_transfer_amount_covers_split_payouts() {
+        assert_eq!(gross_transfer_amount(1_000, 0).unwrap(), 1_000);
+        assert_eq!(gross_transfer_amount(1_000, 100).unwrap(), 1_011);
+        assert_eq!(
+            gross_transfer_amount(1, MAX_BPS),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+
+        // Parts grossed up on their cumulative total add up to the whole.
+        let whole = gross_transfer_amount(1_000, 250).unwrap();
+        let parts: u64 = [0, 1, 7, 300, 999, 1_000]
+            .windows(2)
+            .map(|pair| {
+                gross_transfer_amount(pair[1], 250).unwrap()
+                    - gross_transfer_amount(pair[0], 250).unwrap()
+            })
+            .sum();
+        assert_eq!(parts, whole);
+    }
+
+    fn auction() -> ProjectInfo {
+        ProjectInfo {
+            token_price_numerator: 1,
+            token_price_denominator: 1,
+            auction_start_denominator: 3,
+            auction_clearing_denominator: 3,
+            launch_start_time: 0,
+            launch_end_time: 100,
+            payment_decimals: SOL_DECIMALS,
+            token_decimals: SOL_DECIMALS,
+            ..ProjectInfo::default()
+        }
+    }
+
+    /// Places a bid like `Mint` and returns the buyer's mint info.
+    fn bid(project: &mut ProjectInfo, amount: u64, now: i64) -> MintInfo {
+        let auction_tokens = project.place_auction_bid(amount, now).unwrap();
+        project.total_raised += amount;
+        MintInfo {
+            amount,
+            auction_tokens,
+            ..MintInfo::default()
+        }
+    }
+
+    #[test]
+    fn auction_price_decays_to_the_floor() {
+        let project = auction();
+        assert_eq!(project.auction_price_denominator(-1), 3);
+        assert_eq!(project.auction_price_denominator(0), 3);
+        assert_eq!(project.auction_price_denominator(25), 3);
+        assert_eq!(project.auction_price_denominator(50), 2);
+        assert_eq!(project.auction_price_denominator(99), 2);
+        assert_eq!(project.auction_price_denominator(100), 1);
+        assert_eq!(project.auction_price_denominator(i64::MAX), 1);
+
+        let fixed = priced(1, 7, SOL_DECIMALS);
+        assert_eq!(fixed.auction_price_denominator(0), 7);
+    }
+
+    #[test]
+    fn auction_cost_rounds_up() {
+        let mut project = auction();
+        project.auction_clearing_denominator = 2;
+        assert_eq!(project.auction_cost(500_000_000).unwrap(), 1_000_000_000);
+        project.auction_clearing_denominator = 3;
+        assert_eq!(project.auction_cost(333_333_333).unwrap(), 999_999_999);
+        project.token_price_numerator = 3;
+        project.auction_clearing_denominator = 1;
+        assert_eq!(project.auction_cost(1).unwrap(), 1);
+        assert_eq!(project.auction_cost(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn auction_bidders_settle_at_the_lowest_price() {
+        let mut project = auction();
+        let early = bid(&mut project, 1_000_000_000, 10);
+        let late = bid(&mut project, 1_000_000_000, 60);
+        assert_eq!(project.auction_clearing_denominator, 2);
+        assert_eq!(early.auction_tokens, 333_333_333);
+        assert_eq!(late.auction_tokens, 500_000_000);
+
+        assert_eq!(project.excess_contribution(&early).unwrap(), 333_333_334);
+        assert_eq!(project.excess_contribution(&late).unwrap(), 0);
+        assert_eq!(project.excess_total().unwrap(), 333_333_334);
+    }
+
+    #[test]
+    fn extending_the_auction_keeps_the_lowest_clearing_price() {
+        let mut project = auction();
+        let cheap = bid(&mut project, 1_000_000_000, 50);
+        // The manager pushes the end back, so the price goes up again.
+        project.launch_end_time = 1_000;
+        assert_eq!(project.auction_price_denominator(60), 3);
+        let dear = bid(&mut project, 1_000_000_000, 60);
+        assert_eq!(project.auction_clearing_denominator, 2);
+
+        let owed = project.excess_contribution(&cheap).unwrap()
+            + project.excess_contribution(&dear).unwrap();
+        assert_eq!(owed, 333_333_334);
+        assert!(owed <= project.excess_total().unwrap());
+        assert!(
+            owed + project.auction_cost(project.auction_tokens_sold).unwrap()
+                <= project.total_raised
+        );
+    }
+
+    fn overflow_sale(hard_cap: u64) -> ProjectInfo {
+        ProjectInfo {
+            hard_cap,
+            overflow: true,
+            ..priced(1, 1, SOL_DECIMALS)
+        }
+    }
+
+    /// Adds contributions like `Mint` and returns the buyers' mint infos.
+    fn contribute(project: &mut ProjectInfo, amounts: &[u64]) -> Vec<MintInfo> {
+        amounts
+            .iter()
+            .map(|&amount| {
+                project.total_raised += amount;
+                MintInfo {
+                    amount,
+                    ..MintInfo::default()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn undersubscribed_overflow_sale_keeps_everything() {
+        let mut project = overflow_sale(1_000_000_000);
+        let buyers = contribute(&mut project, &[300_000_000, 700_000_000]);
+        assert!(!project.is_oversubscribed());
+        assert_eq!(project.accepted_total(project.total_raised), 1_000_000_000);
+        assert_eq!(project.excess_total().unwrap(), 0);
+        for buyer in &buyers {
+            assert_eq!(project.excess_contribution(buyer).unwrap(), 0);
+            assert_eq!(
+                project.allocation(buyer, 6).unwrap(),
+                project.token_amount(buyer.amount, 6).unwrap()
+            );
+        }
+        assert_eq!(project.tokens_sold(6).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn sale_without_overflow_is_never_oversubscribed() {
+        let mut project = priced(1, 1, SOL_DECIMALS);
+        project.hard_cap = 1_000;
+        project.total_raised = 5_000;
+        assert!(!project.is_oversubscribed());
+        assert_eq!(project.accepted_total(5_000), 5_000);
+        assert_eq!(project.excess_total().unwrap(), 0);
+    }
+
+    #[test]
+    fn oversubscribed_sale_allocates_pro_rata() {
+        let mut project = overflow_sale(1_000_000_000);
+        let buyers = contribute(&mut project, &[1_000_000_000, 2_000_000_000, 1_000_000_000]);
+        assert!(project.is_oversubscribed());
+        assert_eq!(project.accepted_total(project.total_raised), 1_000_000_000);
+        assert_eq!(project.excess_total().unwrap(), 3_000_000_000);
+        assert_eq!(
+            project.excess_contribution(&buyers[0]).unwrap(),
+            750_000_000
+        );
+        assert_eq!(
+            project.excess_contribution(&buyers[1]).unwrap(),
+            1_500_000_000
+        );
+        assert_eq!(project.allocation(&buyers[0], 6).unwrap(), 250_000);
+        assert_eq!(project.allocation(&buyers[1], 6).unwrap(), 500_000);
+        assert_eq!(project.tokens_sold(6).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn oversubscribed_payouts_never_exceed_the_totals() {
+        let mut project = overflow_sale(1_000_000_007);
+        let buyers = contribute(
+            &mut project,
+            &[333_333_333, 777_777_777, 1_000_000_001, 5, 123_456_789],
+        );
+        assert!(project.is_oversubscribed());
+
+        let mut excess = 0;
+        let mut allocated = 0;
+        for buyer in &buyers {
+            let refund = project.excess_contribution(buyer).unwrap();
+            assert!(refund <= buyer.amount);
+            excess += refund;
+            allocated += project.allocation(buyer, 6).unwrap();
+        }
+        assert!(excess <= project.excess_total().unwrap());
+        assert!(project.excess_total().unwrap() - excess < buyers.len() as u64);
+        assert!(allocated <= project.tokens_sold(6).unwrap());
+        assert!(project.tokens_sold(6).unwrap() - allocated < buyers.len() as u64);
+    }
+
+    fn round(start_time: u64, end_time: u64, token_price_denominator: u64) -> SaleRound {
+        SaleRound {
+            start_time,
+            end_time,
+            token_price_numerator: 1,
+            token_price_denominator,
+            ..SaleRound::default()
+        }
+    }
+
+    fn rounds_sale(rounds: &[SaleRound]) -> ProjectInfo {
+        let mut project = ProjectInfo {
+            launch_start_time: 100,
+            launch_end_time: 400,
+            ..priced(1, 1, SOL_DECIMALS)
+        };
+        assert!(project.set_rounds(rounds));
+        project
+    }
+
+    #[test]
+    fn ordered_rounds_inside_the_launch_window_are_valid() {
+        assert!(rounds_sale(&[]).are_rounds_valid());
+        assert!(
+            rounds_sale(&[round(100, 200, 1), round(200, 300, 2), round(350, 400, 4)])
+                .are_rounds_valid()
+        );
+    }
+
+    #[test]
+    fn misplaced_rounds_are_rejected() {
+        // Overlapping.
+        assert!(!rounds_sale(&[round(100, 250, 1), round(200, 300, 2)]).are_rounds_valid());
+        // Out of order.
+        assert!(!rounds_sale(&[round(200, 300, 1), round(100, 200, 2)]).are_rounds_valid());
+        // Empty.
+        assert!(!rounds_sale(&[round(200, 200, 1)]).are_rounds_valid());
+        // Before the launch window opens, or after it closes.
+        assert!(!rounds_sale(&[round(99, 200, 1)]).are_rounds_valid());
+        assert!(!rounds_sale(&[round(100, 200, 1), round(300, 401, 2)]).are_rounds_valid());
+        // Unpriced.
+        assert!(!rounds_sale(&[round(100, 200, 0)]).are_rounds_valid());
+    }
+
+    #[test]
+    fn rounds_exclude_other_sale_modes() {
+        let rounds = [round(100, 200, 1)];
+        let mut project = rounds_sale(&rounds);
+        project.allowlist_root = [1; 32];
+        assert!(!project.are_rounds_valid());
+
+        let mut project = rounds_sale(&rounds);
+        project.overflow = true;
+        assert!(!project.are_rounds_valid());
+
+        let mut project = rounds_sale(&rounds);
+        project.auction_start_denominator = 2;
+        assert!(!project.are_rounds_valid());
+    }
+
+    #[test]
+    fn set_rounds_resets_raised_amounts() {
+        let mut project = rounds_sale(&[round(100, 200, 1), round(200, 300, 2)]);
+        project.rounds[0].raised = 5;
+        let replacement = SaleRound {
+            raised: 7,
+            ..round(100, 300, 1)
+        };
+        assert!(project.set_rounds(&[replacement]));
+        assert_eq!(project.round_count, 1);
+        assert_eq!(project.rounds()[0].raised, 0);
+        assert_eq!(project.rounds[1], SaleRound::default());
+
+        assert!(!project.set_rounds(&[replacement; MAX_ROUNDS + 1]));
+        assert_eq!(project.round_count, 1);
+    }
+
+    #[test]
+    fn active_round_follows_the_clock() {
+        let project = rounds_sale(&[round(100, 200, 1), round(250, 300, 2)]);
+        assert_eq!(project.active_round(99), None);
+        assert_eq!(project.active_round(100), Some(0));
+        assert_eq!(project.active_round(199), Some(0));
+        assert_eq!(project.active_round(200), None);
+        assert_eq!(project.active_round(250), Some(1));
+        assert_eq!(project.active_round(300), None);
+    }
+
+    #[test]
+    fn allocations_add_up_across_rounds_at_their_prices() {
+        let mut project = rounds_sale(&[round(100, 200, 1), round(200, 300, 2)]);
+        project.rounds[0].raised = 3_000_000_000;
+        project.rounds[1].raised = 4_000_000_000;
+        let mut buyer = MintInfo {
+            amount: 3_000_000_000,
+            ..MintInfo::default()
+        };
+        buyer.round_amounts[0] = 1_000_000_000;
+        buyer.round_amounts[1] = 2_000_000_000;
+
+        assert_eq!(project.allocation(&buyer, 6).unwrap(), 2_000_000);
+        assert_eq!(project.tokens_sold(6).unwrap(), 5_000_000);
+    }
+
+    #[test]
+    fn referral_rewards_shrink_with_the_excess() {
+        let mut project = overflow_sale(1_000_000_000);
+        project.referral_bps = 500;
+        assert_eq!(project.referral_payout(1_000).unwrap(), 0);
+
+        contribute(&mut project, &[1_000_000_000]);
+        project.referral_rewards = project.referral_reward(1_000_000_000);
+        assert_eq!(project.referral_rewards, 50_000_000);
+        assert_eq!(project.referral_payout(50_000_000).unwrap(), 50_000_000);
+
+        // A quarter of the contributions are kept once the sale is oversubscribed.
+        contribute(&mut project, &[3_000_000_000]);
+        project.referral_rewards += project.referral_reward(3_000_000_000);
+        assert_eq!(project.referral_payout(50_000_000).unwrap(), 12_500_000);
+        assert_eq!(project.outstanding_referral_rewards().unwrap(), 50_000_000);
+
+        project.referral_rewards_paid = 12_500_000;
+        assert_eq!(project.outstanding_referral_rewards().unwrap(), 37_500_000);
+    }
+
+    #[test]
+    fn auction_referral_rewards_shrink_with_the_excess() {
+        let mut project = auction();
+        project.referral_bps = 1_000;
+        bid(&mut project, 1_000_000_000, 10);
+        bid(&mut project, 1_000_000_000, 60);
+        project.referral_rewards = project.referral_reward(project.total_raised);
+
+        let kept = project.total_raised - project.excess_total().unwrap();
+        assert_eq!(kept, 1_666_666_666);
+        assert_eq!(project.outstanding_referral_rewards().unwrap(), 166_666_666);
+        assert!(
+            project.referral_payout(project.referral_rewards).unwrap()
+                <= project.referral_reward(kept)
+        );
+    }
+
+    #[test]
+    fn project_size_matches_the_serialized_length() {
+        let project = ProjectInfo {
+            payment_mint: Some(Pubkey::new_unique()),
+            ..ProjectInfo::default()
+        };
+        assert_eq!(project.try_to_vec().unwrap().len(), ProjectInfo::SIZE);
+    }
+}